@@ -3,7 +3,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
     atomic_cards::{
-        cards::Card_Keys,
+        cards::{CardWriter, Card_Keys},
         sqlite::{db_column, SqliteTable, SqliteTableImpl},
         types::WUBRG,
     },
@@ -59,18 +59,10 @@ impl SqliteTable for Cardoid {
     }
 
     fn post_store(&self, id: i64, conn: &Connection) -> anyhow::Result<()> {
-        Card::store_rows(&conn, |mut s| {
-            for c in self {
-                s.store(
-                    c,
-                    &mut Card_Keys {
-                        cardoid: id,
-                        legalities: None,
-                    },
-                )?;
-            }
-            Ok(())
-        })?;
+        let mut writer = CardWriter::new(conn)?;
+        for c in self {
+            writer.write(c, id)?;
+        }
 
         Ok(())
     }
@@ -162,6 +154,18 @@ impl Cardoid {
             _ => 1,
         }
     }
+
+    pub fn foreign_names(&self) -> impl Iterator<Item = &str> {
+        self.iter()
+            .flat_map(|c| &c.foreign_data)
+            .flat_map(|f| [f.name.as_str(), f.face_name.as_str()])
+            .filter(|n| !n.is_empty())
+            .unique()
+    }
+
+    pub fn localized(&self, language: &str) -> Cardoid {
+        Cardoid(self.iter().map(|c| c.localized(language)).collect())
+    }
 }
 
 impl IntoIterator for Cardoid {
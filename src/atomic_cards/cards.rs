@@ -5,11 +5,12 @@ use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 
 use crate::atomic_cards::metadata::ForeignData_Keys;
-use crate::atomic_cards::sqlite::{db_column, SqliteTable, SqliteTableImpl};
+use crate::atomic_cards::sqlite::{db_column, SqliteTable, SqliteTableImpl, Store};
 
 use crate::utils::ToS;
 use rusqlite::ToSql;
 
+use super::mana::ManaCost;
 use super::sqlite;
 use super::types::LeadershipSkills;
 use super::{
@@ -18,7 +19,7 @@ use super::{
     types::{CardLayout, FaceLayout, Side, Supertype, Type, WUBRG},
 };
 
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
 #[cfg(test)]
 use crate::atomic_cards::metadata::Legality;
@@ -62,7 +63,7 @@ pub struct Card {
     #[serde(default, skip_serializing_if = "is_default")]
     pub loyalty: String,
     #[serde(default, skip_serializing_if = "is_default", rename = "manaCost")]
-    pub mana_cost: String,
+    pub mana_cost: ManaCost,
     #[serde(default, skip_serializing_if = "is_default", rename = "manaValue")]
     pub mana_value: f64,
     pub name: String,
@@ -121,7 +122,7 @@ impl SqliteTable for Card {
         ),
         db_column!(object.layout "INTEGER NOT NULL", val.as_i64()),
         db_column!(object.leadership_skills "BLOB NOT NULL", val.as_blob()),
-        db_column!(object.mana_cost "TEXT NOT NULL", val.as_str()),
+        db_column!(object.mana_cost "TEXT NOT NULL", val -> val.as_str()?.into()),
         db_column!(object.mana_value "REAL NOT NULL", val.as_f64()),
         db_column!(INDEX object.name "TEXT NOT NULL", val.as_str()),
         db_column!(object.power "TEXT NOT NULL", val.as_str()),
@@ -145,6 +146,21 @@ impl SqliteTable for Card {
         db_column!(NOINDEX key.legalities "INTEGER NOT NULL", val.as_i64()),
     ];
 
+    // For the searches compiled to SQL, see query::sql. The legalities key
+    // can't be INDEX since it isn't part of the lookups by cardoid.
+    fn create_extras() -> Vec<String> {
+        let table_name = Self::table_name();
+        vec![
+            format!(
+                "CREATE INDEX IF NOT EXISTS {table_name}__legalities ON {table_name} (legalities);"
+            ),
+            format!(
+                "CREATE INDEX IF NOT EXISTS {table_name}__mana_value \
+                 ON {table_name} (coalesce(face_mana_value, mana_value));"
+            ),
+        ]
+    }
+
     fn extra_setup(conn: &Connection) -> anyhow::Result<()> {
         ForeignData::setup(conn)?;
         Legalities::setup(conn)?;
@@ -169,21 +185,62 @@ impl SqliteTable for Card {
     }
 
     fn pre_store(&self, key: &mut Self::Keys, conn: &rusqlite::Connection) -> anyhow::Result<()> {
-        Legalities::store_rows(conn, |mut s| {
-            Ok(key.legalities = Some(s.store(&self.legalities, &mut ())?))
-        })?;
-
+        key.legalities = Some(CardWriter::new(conn)?.legalities(&self.legalities)?);
         Ok(())
     }
 
     fn post_store(&self, id: i64, conn: &Connection) -> anyhow::Result<()> {
-        ForeignData::store_rows(conn, |mut s| {
-            for f in &self.foreign_data {
-                s.store(f, &mut ForeignData_Keys { parent_card: id })?;
-            }
-            Ok(())
-        })?;
+        CardWriter::new(conn)?.foreign_data(self, id)
+    }
+}
+
+// Stores cards along with their legalities and foreign data, for the store
+// hooks and the bulk import alike. Legalities rows repeat a lot, each one is
+// only written once per writer.
+pub struct CardWriter<'a> {
+    cards: Store<'a, Card, Card_Keys>,
+    foreign: Store<'a, ForeignData, ForeignData_Keys>,
+    legalities: Store<'a, Legalities, ()>,
+    legalities_ids: HashMap<Legalities, i64>,
+}
+
+impl<'a> CardWriter<'a> {
+    pub fn new(conn: &'a Connection) -> anyhow::Result<Self> {
+        Ok(Self {
+            cards: Card::inserter(conn)?,
+            foreign: ForeignData::inserter(conn)?,
+            legalities: Legalities::inserter(conn)?,
+            legalities_ids: HashMap::new(),
+        })
+    }
+
+    pub fn write(&mut self, card: &Card, cardoid: i64) -> anyhow::Result<i64> {
+        let legalities = self.legalities(&card.legalities)?;
+        let id = self.cards.insert(
+            card,
+            &Card_Keys {
+                cardoid,
+                legalities: Some(legalities),
+            },
+        )?;
+        self.foreign_data(card, id)?;
+        Ok(id)
+    }
 
+    fn legalities(&mut self, legalities: &Legalities) -> anyhow::Result<i64> {
+        if let Some(id) = self.legalities_ids.get(legalities) {
+            return Ok(*id);
+        }
+        let id = self.legalities.insert(legalities, &())?;
+        self.legalities_ids.insert(legalities.clone(), id);
+        Ok(id)
+    }
+
+    fn foreign_data(&mut self, card: &Card, id: i64) -> anyhow::Result<()> {
+        for f in &card.foreign_data {
+            self.foreign
+                .insert(f, &ForeignData_Keys { parent_card: id })?;
+        }
         Ok(())
     }
 }
@@ -223,17 +280,17 @@ fn card_tests() -> anyhow::Result<()> {
                 x
             },
             loyalty: "".s(),
-            mana_cost: "{1}{W}".s(),
+            mana_cost: "{1}{W/U}".into(),
             mana_value: 2.0,
             name: "Foo".s(),
             power: "1".s(),
             side: Side::B,
             subtypes: vec!["Borb".s()],
-            supertypes: vec![Supertype::Legendary],
+            supertypes: vec![Supertype::Legendary, Supertype::World],
             text: "Flying\nVery good boy.".s(),
             toughness: "2".s(),
-            type_line: "Legendary Creature - Borb".s(),
-            types: vec![Type::Creature],
+            type_line: "Legendary World Tribal Creature - Borb".s(),
+            types: vec![Type::Tribal, Type::Creature],
         },
         Card_Keys {
             cardoid: -1,
@@ -259,6 +316,15 @@ fn card_tests() -> anyhow::Result<()> {
 
     assert_eq!(&legalities[0], &data[0].0.legalities);
 
+    // Translations without a face name keep the English one.
+    let mut card = data[0].0.clone();
+    card.face_name = "Foo".s();
+    card.foreign_data[0].face_name = "".s();
+    let danish = card.localized("danish");
+    assert_eq!(danish.name, "Foo // Bar");
+    assert_eq!(danish.face_name, "Foo");
+    assert_eq!(danish.type_line, "Land");
+
     Ok(())
 }
 
@@ -366,6 +432,31 @@ impl Card {
             self.mana_value as usize
         }
     }
+
+    pub fn foreign(&self, language: &str) -> Option<&ForeignData> {
+        self.foreign_data
+            .iter()
+            .find(|f| f.language.eq_ignore_ascii_case(language))
+    }
+
+    pub fn localized(&self, language: &str) -> Card {
+        let mut res = self.clone();
+
+        if let Some(foreign) = self.foreign(language) {
+            let replace = |field: &mut String, value: &String| {
+                if !value.is_empty() {
+                    *field = value.clone();
+                }
+            };
+
+            replace(&mut res.name, &foreign.name);
+            replace(&mut res.face_name, &foreign.face_name);
+            replace(&mut res.type_line, &foreign.type_line);
+            replace(&mut res.text, &foreign.text);
+        }
+
+        res
+    }
 }
 
 impl Display for Card {
@@ -0,0 +1,120 @@
+use itertools::{EitherOrBoth, Itertools};
+
+use crate::{
+    atomic_cards::{cardoids::Cardoid, cards::Card},
+    utils::ToS,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub face: String,
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl Cardoid {
+    pub fn diff(&self, newer: &Cardoid) -> Vec<Change> {
+        let mut res = vec![];
+
+        for pair in self.iter().zip_longest(newer.iter()) {
+            match pair {
+                EitherOrBoth::Both(old, new) => Card::diff_into(old, new, &mut res),
+                EitherOrBoth::Left(old) => res.push(Change {
+                    face: old.get_name(),
+                    field: "face".s(),
+                    old: old.get_name(),
+                    new: "".s(),
+                }),
+                EitherOrBoth::Right(new) => res.push(Change {
+                    face: new.get_name(),
+                    field: "face".s(),
+                    old: "".s(),
+                    new: new.get_name(),
+                }),
+            }
+        }
+
+        res
+    }
+}
+
+impl Card {
+    fn diff_into(old: &Card, new: &Card, res: &mut Vec<Change>) {
+        let face = new.get_name();
+
+        let mut changed = |field: &str, old: &str, new: &str| {
+            if old != new {
+                res.push(Change {
+                    face: face.clone(),
+                    field: field.s(),
+                    old: old.s(),
+                    new: new.s(),
+                })
+            }
+        };
+
+        changed("name", &old.get_name(), &new.get_name());
+        changed("text", &old.text, &new.text);
+        changed("type", &old.type_line, &new.type_line);
+        changed("power", &old.power, &new.power);
+        changed("toughness", &old.toughness, &new.toughness);
+
+        for ((format, old), (_, new)) in old
+            .legalities
+            .formats()
+            .into_iter()
+            .zip(new.legalities.formats())
+        {
+            changed(
+                &format!("legalities.{format}"),
+                &old.to_string(),
+                &new.to_string(),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_cardoid_diff() {
+    use crate::atomic_cards::metadata::{Legalities, Legality};
+
+    let old = Card {
+        name: "Foo".s(),
+        text: "Flying".s(),
+        type_line: "Creature — Bird".s(),
+        power: "1".s(),
+        toughness: "1".s(),
+        legalities: Legalities {
+            commander: Legality::Legal,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut new = old.clone();
+    new.toughness = "2".s();
+    new.legalities.commander = Legality::Banned;
+
+    let old = Cardoid::from(vec![old]);
+    let new = Cardoid::from(vec![new]);
+
+    assert_eq!(old.diff(&old), vec![]);
+    assert_eq!(
+        old.diff(&new),
+        vec![
+            Change {
+                face: "Foo".s(),
+                field: "toughness".s(),
+                old: "1".s(),
+                new: "2".s(),
+            },
+            Change {
+                face: "Foo".s(),
+                field: "legalities.commander".s(),
+                old: "Legal".s(),
+                new: "Banned".s(),
+            },
+        ]
+    );
+}
@@ -1,36 +1,101 @@
-use std::time::Instant;
+use std::{io::Read, path::Path, time::Instant};
 
+use anyhow::anyhow;
+use clap::ValueEnum;
 use rusqlite::Connection;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 
 use crate::atomic_cards::{
     cardoids::{Cardoid, Cardoid_Keys},
+    cards::CardWriter,
+    fulltext,
     metadata::MetaData,
+    schema::Schema,
+    source::CardSource,
     sqlite::SqliteTableImpl,
     AtomicCardsDb, AtomicCardsFile,
 };
 
+#[cfg(test)]
+use crate::{
+    atomic_cards::{
+        cards::Card,
+        metadata::{ForeignData, Legalities},
+    },
+    utils::ToS,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Compression {
+    None,
+    #[default]
+    Gz,
+    Xz,
+}
+
+impl Compression {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gz => ".gz",
+            Compression::Xz => ".xz",
+        }
+    }
+
+    pub fn decompress(self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut res = vec![];
+        match self {
+            Compression::None => res.extend_from_slice(data),
+            Compression::Gz => {
+                flate2::read::GzDecoder::new(data).read_to_end(&mut res)?;
+            }
+            Compression::Xz => {
+                lzma_rs::xz_decompress(&mut std::io::BufReader::new(data), &mut res)
+                    .map_err(|e| anyhow!("Failed to decompress: {e}"))?;
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Deserialize)]
+struct MetaFile {
+    data: MetaData,
+}
+
 impl AtomicCardsFile {
-    pub fn load_raw_file(verbose: bool) -> anyhow::Result<Vec<u8>> {
-        if !(std::fs::exists(Self::ATOMIC_CARDS_DUMP)?) {
-            return Ok(std::fs::read(Self::ATOMIC_CARDS_DUMP)?);
+    pub fn load_raw_file(dir: &Path, verbose: bool) -> anyhow::Result<Vec<u8>> {
+        let dump = dir.join(Self::ATOMIC_CARDS_DUMP);
+        if std::fs::exists(&dump)? {
+            return Ok(std::fs::read(&dump)?);
         }
 
-        let start = Instant::now();
         if verbose {
-            eprintln!("{} file not found, downloading...", Self::ATOMIC_CARDS_DUMP);
+            eprintln!("{} file not found, downloading...", dump.display());
         }
 
+        Self::download(dir, Self::ATOMIC_CARDS_URL, verbose)
+    }
+
+    fn fetch(url: &str) -> anyhow::Result<Vec<u8>> {
         let client = reqwest::blocking::ClientBuilder::new()
             .timeout(None)
             .build()?;
 
-        let request = client.get(Self::ATOMIC_CARDS_URL).build()?;
+        let request = client.get(url).build()?;
+
+        let response = client.execute(request)?.error_for_status()?;
+
+        Ok(response.bytes()?.to_vec())
+    }
 
-        let response = client.execute(request)?;
+    pub fn download(dir: &Path, url: &str, verbose: bool) -> anyhow::Result<Vec<u8>> {
+        let start = Instant::now();
 
-        let downloaded = response.bytes()?.to_vec();
+        let downloaded = Self::fetch(url)?;
 
-        std::fs::write(Self::ATOMIC_CARDS_DUMP, &downloaded)?;
+        std::fs::write(dir.join(Self::ATOMIC_CARDS_DUMP), &downloaded)?;
 
         if verbose {
             eprintln!(
@@ -40,28 +105,103 @@ impl AtomicCardsFile {
             );
         }
 
-        return Ok(downloaded);
+        Ok(downloaded)
+    }
+
+    pub fn fetch_meta(base_url: &str) -> anyhow::Result<MetaData> {
+        let url = format!("{}/{}", base_url.trim_end_matches('/'), Self::META_FILE);
+        let meta: MetaFile = serde_json::from_slice(&Self::fetch(&url)?)?;
+        Ok(meta.data)
     }
 
-    pub fn load_json(verbose: bool) -> anyhow::Result<Self> {
+    pub fn download_verified(
+        dir: &Path,
+        base_url: &str,
+        compression: Compression,
+        verbose: bool,
+    ) -> anyhow::Result<Vec<u8>> {
         let start = Instant::now();
 
-        let atomic_cards = if std::fs::exists(Self::ATOMIC_CARDS_FILE)? {
-            let data = std::fs::read(Self::ATOMIC_CARDS_FILE)?;
+        let url = format!(
+            "{}/{}{}",
+            base_url.trim_end_matches('/'),
+            Self::ATOMIC_CARDS_FILE,
+            compression.extension()
+        );
 
-            serde_json::from_slice(&data)?
-        } else {
-            let data = Self::load_raw_file(verbose)?;
+        let packed = Self::fetch(&url)?;
+        let checksum = String::from_utf8(Self::fetch(&format!("{url}.sha256"))?)?;
 
+        let expected = checksum
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let actual = Sha256::digest(&packed)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+
+        if expected != actual {
+            return Err(anyhow!(
+                "Checksum mismatch for {url}: expected {expected}, got {actual}"
+            ));
+        }
+
+        let downloaded = compression.decompress(&packed)?;
+
+        std::fs::write(dir.join(Self::ATOMIC_CARDS_DUMP), &downloaded)?;
+
+        if verbose {
+            eprintln!(
+                "Downloaded and verified {} megabytes in {} seconds.",
+                packed.len() / 1024 / 1000,
+                start.elapsed().as_secs()
+            );
+        }
+
+        Ok(downloaded)
+    }
+
+    pub fn save_json(&self, dir: &Path) -> anyhow::Result<()> {
+        std::fs::write(dir.join(Self::ATOMIC_CARDS_FILE), serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    pub fn load_json(dir: &Path, verbose: bool) -> anyhow::Result<Self> {
+        let start = Instant::now();
+        let path = dir.join(Self::ATOMIC_CARDS_FILE);
+
+        let snapshot = Self::load_snapshot(dir).unwrap_or_else(|e| {
             if verbose {
-                eprintln!("{} not found, recreating...", Self::ATOMIC_CARDS_FILE);
+                eprintln!("Ignoring the card snapshot: {}", e);
             }
+            None
+        });
 
+        let atomic_cards = if let Some(atomic_cards) = snapshot {
+            if verbose {
+                eprintln!("Reading {} from its snapshot.", path.display());
+            }
+            atomic_cards
+        } else if std::fs::exists(&path)? {
+            let data = std::fs::read(&path)?;
             let atomic_cards: AtomicCardsFile = serde_json::from_slice(&data)?;
 
-            let data = serde_json::to_vec(&atomic_cards)?;
+            atomic_cards.try_save_snapshot(dir);
+
+            atomic_cards
+        } else {
+            let data = Self::load_raw_file(dir, verbose)?;
+
+            if verbose {
+                eprintln!("{} not found, recreating...", path.display());
+            }
+
+            let atomic_cards: AtomicCardsFile = serde_json::from_slice(&data)?;
 
-            std::fs::write(Self::ATOMIC_CARDS_FILE, data)?;
+            atomic_cards.save_json(dir)?;
+            atomic_cards.try_save_snapshot(dir);
 
             atomic_cards
         };
@@ -69,7 +209,7 @@ impl AtomicCardsFile {
         if verbose {
             eprintln!(
                 "Loaded {}, {} cards in {} milliseconds.",
-                Self::ATOMIC_CARDS_FILE,
+                path.display(),
                 atomic_cards.data.len(),
                 start.elapsed().as_millis()
             );
@@ -80,11 +220,11 @@ impl AtomicCardsFile {
 }
 
 impl AtomicCardsDb {
-    #[allow(unused)]
-    pub fn load_sqlite() -> anyhow::Result<AtomicCardsDb> {
-        if std::fs::exists(Self::ATOMIC_CARDS_DB)? {
+    pub fn load_sqlite(dir: &Path) -> anyhow::Result<AtomicCardsDb> {
+        let path = dir.join(Self::ATOMIC_CARDS_DB);
+        if std::fs::exists(&path)? {
             Ok(AtomicCardsDb {
-                conn: Connection::open(Self::ATOMIC_CARDS_DB)?,
+                conn: Connection::open(&path)?,
             })
         } else {
             Ok(AtomicCardsDb {
@@ -93,34 +233,338 @@ impl AtomicCardsDb {
         }
     }
 
-    #[allow(unused)]
-    pub fn initialize(&self, file: &AtomicCardsFile) -> anyhow::Result<()> {
-        MetaData::setup(&self.conn)?;
-        Cardoid::setup(&self.conn)?;
-
-        MetaData::store_rows(&self.conn, |mut s| {
-            s.store(&file.meta, &mut ())?;
-            Ok(())
-        })?;
-
-        Cardoid::store_rows(&self.conn, |mut s| {
-            for (n, c) in &file.data {
-                s.store(
-                    c,
-                    &mut Cardoid_Keys {
-                        card_name: n.clone(),
-                    },
-                )?;
+    pub fn installed_meta(dir: &Path) -> anyhow::Result<Option<MetaData>> {
+        if std::fs::exists(dir.join(Self::ATOMIC_CARDS_DB))? {
+            Ok(Some(Self::load_sqlite(dir)?.meta()?))
+        } else if std::fs::exists(dir.join(AtomicCardsFile::ATOMIC_CARDS_FILE))? {
+            Ok(Some(AtomicCardsFile::load_json(dir, false)?.meta))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn load_or_build(dir: &Path, verbose: bool) -> anyhow::Result<AtomicCardsDb> {
+        let start = Instant::now();
+        let path = dir.join(Self::ATOMIC_CARDS_DB);
+
+        let existing = if std::fs::exists(&path)? {
+            Some(Self::load_sqlite(dir)?)
+        } else {
+            None
+        };
+
+        let db = match existing {
+            Some(db) if db.is_current()? => db,
+            existing => {
+                let atomic_cards = AtomicCardsFile::load_json(dir, verbose)?;
+
+                if existing.is_some() {
+                    eprintln!(
+                        "{} was built with a different schema, rebuilding...",
+                        path.display()
+                    );
+                } else if verbose {
+                    eprintln!("{} not found, recreating...", path.display());
+                }
+                drop(existing);
+
+                Self::build(dir, &atomic_cards, verbose)?
+            }
+        };
+
+        if verbose {
+            eprintln!(
+                "Loaded {} in {} milliseconds.",
+                path.display(),
+                start.elapsed().as_millis()
+            );
+        }
+
+        Ok(db)
+    }
+
+    pub fn build(
+        dir: &Path,
+        file: &AtomicCardsFile,
+        verbose: bool,
+    ) -> anyhow::Result<AtomicCardsDb> {
+        let db = AtomicCardsDb {
+            conn: Connection::open_in_memory()?,
+        };
+        db.initialize(file, verbose)?;
+        db.save(dir)?;
+        Ok(db)
+    }
+
+    // One transaction, and one CardWriter for every card so each distinct
+    // Legalities row is only written once.
+    pub fn initialize(&self, file: &AtomicCardsFile, verbose: bool) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        MetaData::setup(&tx)?;
+        Schema::setup(&tx)?;
+        Cardoid::setup(&tx)?;
+
+        MetaData::inserter(&tx)?.insert(&file.meta, &())?;
+        Schema::inserter(&tx)?.insert(&Schema::current(), &())?;
+
+        let mut cardoids = Cardoid::inserter(&tx)?;
+        let mut cards = CardWriter::new(&tx)?;
+
+        let total = file.data.len();
+        for (i, (name, cardoid)) in file.data.iter().enumerate() {
+            let cardoid_id = cardoids.insert(
+                cardoid,
+                &Cardoid_Keys {
+                    card_name: name.clone(),
+                },
+            )?;
+
+            for card in cardoid {
+                cards.write(card, cardoid_id)?;
+            }
+
+            if verbose && (i % 1000 == 0 || i + 1 == total) {
+                eprint!("\rImporting cards: {}/{}", i + 1, total);
             }
-            Ok(())
-        })?;
+        }
+        if verbose {
+            eprintln!();
+        }
 
+        fulltext::build(&tx, |f| file.data.for_each_cardoid(f))?;
+
+        drop((cardoids, cards));
+        tx.commit()?;
         Ok(())
     }
 
-    #[allow(unused)]
-    pub fn save(&self) -> anyhow::Result<()> {
-        self.conn.backup("main", Self::ATOMIC_CARDS_DB, None)?;
+    pub fn save(&self, dir: &Path) -> anyhow::Result<()> {
+        self.conn
+            .backup("main", dir.join(Self::ATOMIC_CARDS_DB), None)?;
         Ok(())
     }
 }
+
+#[test]
+fn test_atomic_cards_db() -> anyhow::Result<()> {
+    use crate::atomic_cards::{
+        cards::Card,
+        metadata::{ForeignData, Legalities, Legality},
+    };
+
+    let card = |name: &str, legacy: Legality| Card {
+        name: name.s(),
+        foreign_data: vec![ForeignData {
+            language: "Danish".s(),
+            name: name.to_uppercase(),
+            ..Default::default()
+        }],
+        legalities: Legalities {
+            legacy,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut file = AtomicCardsFile::default();
+    file.meta.version = "5.2.2".s();
+    for (name, legacy) in [
+        ("Foo", Legality::Legal),
+        ("Bar", Legality::Banned),
+        ("Baz", Legality::Legal),
+    ] {
+        file.data
+            .insert(name.s(), Cardoid::from(vec![card(name, legacy)]));
+    }
+
+    let db = AtomicCardsDb {
+        conn: Connection::open_in_memory()?,
+    };
+    db.initialize(&file, false)?;
+
+    for (name, cardoid) in &file.data {
+        assert_eq!(db.lookup(name)?.as_ref(), Some(cardoid));
+    }
+    assert_eq!(db.lookup("Qux")?, None);
+
+    Ok(())
+}
+
+// Roughly the size of the real AtomicCards.json: ~30k cardoids, some with two
+// faces, tens of thousands of foreign names and a few hundred distinct
+// legalities. Release and test builds are optimized, see Cargo.toml. The full
+// size only runs with cargo test -- --ignored, it has to stay under 3s.
+#[test]
+#[ignore = "benchmark"]
+fn bench_atomic_cards_import() -> anyhow::Result<()> {
+    check_import_time(30_000, std::time::Duration::from_secs(3))
+}
+
+// A tenth of the benchmark with a loose budget, so a big slowdown still shows
+// up in the default test run.
+#[test]
+fn test_atomic_cards_import_time() -> anyhow::Result<()> {
+    check_import_time(3_000, std::time::Duration::from_secs(1))
+}
+
+#[cfg(test)]
+fn check_import_time(cardoids: usize, target: std::time::Duration) -> anyhow::Result<()> {
+    use crate::atomic_cards::{metadata::Legality, types::Side};
+
+    let legalities = |i: usize| {
+        let legality = |bit: usize| match (i >> bit) % 3 {
+            0 => Legality::Legal,
+            1 => Legality::Banned,
+            _ => Legality::NotLegal,
+        };
+        Legalities {
+            commander: legality(0),
+            legacy: legality(1),
+            vintage: legality(2),
+            modern: legality(3),
+            pauper: legality(4),
+            ..Default::default()
+        }
+    };
+
+    let mut file = AtomicCardsFile::default();
+    for i in 0..cardoids {
+        let name = format!("Card {}", i);
+        let faces = if i % 10 == 0 { 2 } else { 1 };
+        let cards = (0..faces)
+            .map(|face| Card {
+                name: name.clone(),
+                side: if face == 0 { Side::A } else { Side::B },
+                text: format!("When this creature enters, draw {} cards.", i % 7),
+                type_line: "Creature — Elf Druid".s(),
+                legalities: legalities(i % 300),
+                foreign_data: ["German", "French", "Japanese"]
+                    .into_iter()
+                    .take(i % 4)
+                    .map(|language| ForeignData {
+                        language: language.s(),
+                        name: format!("{} in {}", name, language),
+                        text: format!("Translated text of {}", name),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        file.data.insert(name, Cardoid::from(cards));
+    }
+
+    let db = AtomicCardsDb {
+        conn: Connection::open_in_memory()?,
+    };
+    let start = Instant::now();
+    db.initialize(&file, false)?;
+    let elapsed = start.elapsed();
+
+    eprintln!("Imported {} cardoids in {:?}", cardoids, elapsed);
+    assert!(elapsed < target, "Import took {:?}", elapsed);
+
+    assert_eq!(db.card_count()?, cardoids);
+    for i in [0, cardoids / 2 + 1] {
+        let name = format!("Card {}", i);
+        assert_eq!(db.lookup(&name)?.as_ref(), file.data.get(&name));
+    }
+    let distinct = std::collections::HashSet::<&Legalities>::from_iter(
+        file.data
+            .values()
+            .flat_map(|c| c.iter().map(|c| &c.legalities)),
+    );
+    let stored: usize = db
+        .conn
+        .query_row("SELECT count(*) FROM Legalities;", [], |r| r.get(0))?;
+    assert_eq!(stored, distinct.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_download_verified() -> anyhow::Result<()> {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    let raw = br#"{"meta":{"date":"2025-06-01","version":"5.2.2+20250601"},"data":{}}"#;
+
+    let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    gz.write_all(raw)?;
+    let gz = gz.finish()?;
+    let sha = Sha256::digest(&gz)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+
+    let files = [
+        (
+            "/good/Meta.json",
+            br#"{"meta":{"date":"2025-06-01","version":"5.2.2+20250601"},"data":{"date":"2025-06-01","version":"5.2.2+20250601"}}"#.to_vec(),
+        ),
+        ("/good/AtomicCards.json.gz", gz.clone()),
+        (
+            "/good/AtomicCards.json.gz.sha256",
+            format!("{sha}  AtomicCards.json.gz\n").into_bytes(),
+        ),
+        ("/bad/AtomicCards.json.gz", gz),
+        ("/bad/AtomicCards.json.gz.sha256", "0123abcd".into()),
+    ];
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let base = format!("http://{}", listener.local_addr()?);
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut request = String::new();
+            let _ = BufReader::new(&stream).read_line(&mut request);
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = match files.iter().find(|(p, _)| *p == path) {
+                Some((_, body)) => ("200 OK", body.clone()),
+                None => ("404 Not Found", vec![]),
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(&body);
+        }
+    });
+
+    let dir = std::env::temp_dir().join(format!("proxygenitus-download-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    let meta = AtomicCardsFile::fetch_meta(&format!("{base}/good/"))?;
+    assert_eq!(meta.version, "5.2.2+20250601");
+
+    let downloaded =
+        AtomicCardsFile::download_verified(&dir, &format!("{base}/good"), Compression::Gz, false)?;
+    assert_eq!(downloaded, raw);
+    assert_eq!(
+        std::fs::read(dir.join(AtomicCardsFile::ATOMIC_CARDS_DUMP))?,
+        raw
+    );
+
+    assert!(AtomicCardsFile::download_verified(
+        &dir,
+        &format!("{base}/bad"),
+        Compression::Gz,
+        false
+    )
+    .is_err());
+    assert!(AtomicCardsFile::download_verified(
+        &dir,
+        &format!("{base}/none"),
+        Compression::Xz,
+        false
+    )
+    .is_err());
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use itertools::Itertools;
+use rusqlite::Connection;
+
+use crate::atomic_cards::cardoids::Cardoid;
+
+// An FTS5 table over names, type lines and rules text, one row per cardoid.
+// The porter tokenizer stems words so "draws" finds "draw", and the query
+// syntax gives phrases ("draw a card"), prefixes (exil*), NEAR, AND/OR/NOT
+// and column filters (type_line: elf).
+const TABLE: &str = "CardText";
+
+// bm25 weights per column: card_name (unindexed), name, type_line, text.
+const WEIGHTS: &str = "0.0, 10.0, 3.0, 1.0";
+
+pub fn exists(conn: &Connection) -> anyhow::Result<bool> {
+    Ok(conn.query_row(
+        "SELECT count(*) > 0 FROM sqlite_master WHERE name = ?1;",
+        [TABLE],
+        |r| r.get(0),
+    )?)
+}
+
+pub fn create_table_stmt() -> String {
+    format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS {TABLE} USING fts5(\
+         card_name UNINDEXED, name, type_line, text, \
+         tokenize = 'porter unicode61 remove_diacritics 2');"
+    )
+}
+
+pub fn build<F>(conn: &Connection, for_each_cardoid: F) -> anyhow::Result<()>
+where
+    F: FnOnce(&mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>) -> anyhow::Result<()>,
+{
+    // Part of the import's transaction when there is one.
+    let tx = match conn.is_autocommit() {
+        true => Some(conn.unchecked_transaction()?),
+        false => None,
+    };
+
+    conn.execute(&create_table_stmt(), [])?;
+
+    {
+        let mut stmt = conn.prepare(&format!(
+            "INSERT INTO {TABLE} (card_name, name, type_line, text) VALUES (?1, ?2, ?3, ?4);"
+        ))?;
+
+        for_each_cardoid(&mut |card_name, cardoid| {
+            let names = [card_name, cardoid.name()]
+                .into_iter()
+                .chain(cardoid.iter().map(|c| c.face_name.as_str()))
+                .filter(|n| !n.is_empty())
+                .unique()
+                .join("\n");
+            let type_line = cardoid.iter().map(|c| &c.type_line).join("\n");
+            let text = cardoid.iter().map(|c| &c.text).join("\n");
+
+            stmt.execute([card_name, &names, &type_line, &text])?;
+            Ok(())
+        })?;
+    }
+
+    if let Some(tx) = tx {
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+// Best match first, with scores that grow with relevance.
+pub fn search(conn: &Connection, query: &str) -> anyhow::Result<Vec<(String, f64)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT card_name, -bm25({TABLE}, {WEIGHTS}) AS score FROM {TABLE} \
+         WHERE {TABLE} MATCH ?1 ORDER BY score DESC, card_name;"
+    ))?;
+
+    let res = stmt
+        .query_map([query], |r| Ok((r.get(0)?, r.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow::anyhow!("Invalid text query '{}': {}", query, e));
+    res
+}
+
+#[test]
+fn test_text_search() -> anyhow::Result<()> {
+    use crate::atomic_cards::cards::Card;
+    use crate::utils::ToS;
+    use indexmap::IndexMap;
+
+    let card = |name: &str, type_line: &str, text: &str| {
+        Cardoid::from(vec![Card {
+            name: name.s(),
+            type_line: type_line.s(),
+            text: text.s(),
+            ..Default::default()
+        }])
+    };
+    let cards = IndexMap::from([
+        (
+            "Divination".s(),
+            card("Divination", "Sorcery", "Draw two cards."),
+        ),
+        (
+            "Phyrexian Arena".s(),
+            card(
+                "Phyrexian Arena",
+                "Enchantment",
+                "At the beginning of your upkeep, you draw a card and you lose 1 life.",
+            ),
+        ),
+        (
+            "Elvish Visionary".s(),
+            card(
+                "Elvish Visionary",
+                "Creature — Elf Shaman",
+                "When this creature enters, draw a card.",
+            ),
+        ),
+        (
+            "Llanowar Elves".s(),
+            card("Llanowar Elves", "Creature — Elf Druid", "{T}: Add {G}."),
+        ),
+    ]);
+
+    let conn = Connection::open_in_memory()?;
+    assert!(!exists(&conn)?);
+    build(&conn, |f| cards.iter().try_for_each(|(n, c)| f(n, c)))?;
+    assert!(exists(&conn)?);
+
+    let names = |query: &str| -> anyhow::Result<Vec<String>> {
+        Ok(search(&conn, query)?.into_iter().map(|(n, _)| n).collect())
+    };
+
+    assert_eq!(
+        names("draws")?,
+        ["Divination", "Elvish Visionary", "Phyrexian Arena"]
+    );
+    assert_eq!(
+        names("\"draw a card\"")?,
+        ["Elvish Visionary", "Phyrexian Arena"]
+    );
+    assert_eq!(names("visionary OR draw")?[0], "Elvish Visionary");
+    assert_eq!(names("type_line: elf NOT druid")?, ["Elvish Visionary"]);
+    assert!(search(&conn, "\"draw").is_err());
+
+    Ok(())
+}
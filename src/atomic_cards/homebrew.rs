@@ -0,0 +1,320 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use indexmap::{IndexMap, IndexSet};
+
+use rusqlite::Connection;
+
+use crate::{
+    atomic_cards::{cardoids::Cardoid, fulltext, metadata::MetaData, source::CardSource},
+    query::Query,
+};
+
+pub struct HomebrewFile {
+    pub path: PathBuf,
+    pub data: IndexMap<String, Cardoid>,
+}
+
+impl HomebrewFile {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read(path)?;
+        Ok(Self {
+            path: path.to_owned(),
+            data: serde_json::from_slice(&data)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub name: String,
+    pub file: PathBuf,
+    pub overrides: Option<PathBuf>,
+}
+
+impl Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.overrides {
+            Some(other) => write!(
+                f,
+                "{} in {} overrides the card from {}",
+                self.name,
+                self.file.display(),
+                other.display()
+            ),
+            None => write!(
+                f,
+                "{} in {} overrides the official card",
+                self.name,
+                self.file.display()
+            ),
+        }
+    }
+}
+
+pub struct HomebrewCards {
+    official: Box<dyn CardSource>,
+    files: Vec<HomebrewFile>,
+    collisions: Vec<Collision>,
+    // Text index of the homebrew cards, the official ones have their own.
+    index: Connection,
+}
+
+impl HomebrewCards {
+    pub fn new(official: Box<dyn CardSource>, files: Vec<HomebrewFile>) -> anyhow::Result<Self> {
+        let mut collisions = vec![];
+
+        for (i, file) in files.iter().enumerate() {
+            for name in file.data.keys() {
+                let overrides = files[..i].iter().rev().find(|f| f.data.contains_key(name));
+
+                if let Some(other) = overrides {
+                    collisions.push(Collision {
+                        name: name.clone(),
+                        file: file.path.clone(),
+                        overrides: Some(other.path.clone()),
+                    });
+                } else if official.lookup(name)?.is_some() {
+                    collisions.push(Collision {
+                        name: name.clone(),
+                        file: file.path.clone(),
+                        overrides: None,
+                    });
+                }
+            }
+        }
+
+        let res = Self {
+            official,
+            files,
+            collisions,
+            index: Connection::open_in_memory()?,
+        };
+        fulltext::build(&res.index, |f| res.visit(|_| Ok(()), f))?;
+        Ok(res)
+    }
+
+    pub fn load(official: Box<dyn CardSource>, paths: &[PathBuf]) -> anyhow::Result<Self> {
+        let files = paths
+            .iter()
+            .map(|p| HomebrewFile::load(p))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Self::new(official, files)
+    }
+
+    pub fn collisions(&self) -> &[Collision] {
+        &self.collisions
+    }
+
+    fn homebrew(&self, cardname: &str) -> Option<&Cardoid> {
+        self.files.iter().rev().find_map(|f| f.data.get(cardname))
+    }
+
+    // Homebrew cards first, then the official ones they don't override.
+    fn visit<F>(
+        &self,
+        official: F,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()>
+    where
+        F: FnOnce(&mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>) -> anyhow::Result<()>,
+    {
+        let mut seen = IndexSet::new();
+
+        for file in self.files.iter().rev() {
+            for (name, cardoid) in &file.data {
+                if seen.insert(name.as_str()) {
+                    mapper(name, cardoid)?;
+                }
+            }
+        }
+
+        official(&mut |name, cardoid| {
+            if seen.contains(name) {
+                Ok(())
+            } else {
+                mapper(name, cardoid)
+            }
+        })
+    }
+}
+
+impl CardSource for HomebrewCards {
+    fn lookup(&self, cardname: &str) -> anyhow::Result<Option<Cardoid>> {
+        match self.homebrew(cardname) {
+            Some(cardoid) => Ok(Some(cardoid.clone())),
+            None => self.official.lookup(cardname),
+        }
+    }
+
+    fn lookup_many(&self, cardnames: &[&str]) -> anyhow::Result<IndexMap<String, Cardoid>> {
+        let (homebrew, official): (Vec<_>, Vec<_>) = cardnames
+            .iter()
+            .copied()
+            .partition(|name| self.homebrew(name).is_some());
+
+        let mut res = self.official.lookup_many(&official)?;
+        for name in homebrew {
+            if let Some(cardoid) = self.homebrew(name) {
+                res.insert(name.to_string(), cardoid.clone());
+            }
+        }
+        Ok(res)
+    }
+
+    fn for_each_cardoid(
+        &self,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.visit(|f| self.official.for_each_cardoid(f), mapper)
+    }
+
+    fn for_each_matching(
+        &self,
+        query: &Query,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.visit(|f| self.official.for_each_matching(query, f), mapper)
+    }
+
+    fn meta(&self) -> anyhow::Result<MetaData> {
+        self.official.meta()
+    }
+
+    // Results from the homebrew index merged with the official ones by score.
+    fn text_search(&self, query: &str) -> anyhow::Result<Vec<(String, f64)>> {
+        let mut res = fulltext::search(&self.index, query)?;
+        res.extend(
+            self.official
+                .text_search(query)?
+                .into_iter()
+                .filter(|(name, _)| self.homebrew(name).is_none()),
+        );
+        res.sort_by(|(a_name, a), (b_name, b)| b.total_cmp(a).then_with(|| a_name.cmp(b_name)));
+        Ok(res)
+    }
+
+    fn foreign_names(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let mut res = vec![];
+        let mut seen = IndexSet::new();
+
+        for file in self.files.iter().rev() {
+            for (name, cardoid) in &file.data {
+                if seen.insert(name.as_str()) {
+                    res.extend(
+                        cardoid
+                            .foreign_names()
+                            .map(|n| (name.clone(), n.to_owned())),
+                    );
+                }
+            }
+        }
+
+        for (name, foreign) in self.official.foreign_names()? {
+            if !seen.contains(name.as_str()) {
+                res.push((name, foreign));
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn card_count(&self) -> anyhow::Result<usize> {
+        let mut res = self.official.card_count()?;
+        let mut seen = IndexSet::new();
+        for file in &self.files {
+            for name in file.data.keys() {
+                if seen.insert(name) && self.official.lookup(name)?.is_none() {
+                    res += 1;
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[test]
+fn test_homebrew_cards() -> anyhow::Result<()> {
+    use crate::{atomic_cards::cards::Card, utils::ToS};
+
+    let card = |name: &str, text: &str| {
+        (
+            name.s(),
+            Cardoid::from(vec![Card {
+                name: name.s(),
+                text: text.s(),
+                ..Default::default()
+            }]),
+        )
+    };
+
+    let official = IndexMap::from_iter([card("Forest", "official"), card("Island", "official")]);
+
+    let files = vec![
+        HomebrewFile {
+            path: "first.json".into(),
+            data: IndexMap::from_iter([card("Forest", "first"), card("Gremlin", "first")]),
+        },
+        HomebrewFile {
+            path: "second.json".into(),
+            data: IndexMap::from_iter([card("Gremlin", "second")]),
+        },
+    ];
+
+    let cards = HomebrewCards::new(Box::new(official), files)?;
+
+    assert_eq!(
+        cards.collisions(),
+        &[
+            Collision {
+                name: "Forest".s(),
+                file: "first.json".into(),
+                overrides: None,
+            },
+            Collision {
+                name: "Gremlin".s(),
+                file: "second.json".into(),
+                overrides: Some("first.json".into()),
+            },
+        ]
+    );
+
+    let text = |name: &str| -> anyhow::Result<String> {
+        Ok(cards.lookup(name)?.unwrap().face().text.clone())
+    };
+
+    assert_eq!(text("Forest")?, "first");
+    assert_eq!(text("Gremlin")?, "second");
+    assert_eq!(text("Island")?, "official");
+
+    let mut seen = vec![];
+    cards.for_each_cardoid(&mut |name, c| {
+        seen.push((name.s(), c.face().text.clone()));
+        Ok(())
+    })?;
+    assert_eq!(
+        seen,
+        vec![
+            ("Gremlin".s(), "second".s()),
+            ("Forest".s(), "first".s()),
+            ("Island".s(), "official".s()),
+        ]
+    );
+    assert_eq!(cards.card_count()?, 3);
+
+    // Overridden official text doesn't turn up, the homebrew one does.
+    let found = |query: &str| -> anyhow::Result<Vec<String>> {
+        Ok(cards
+            .text_search(query)?
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect())
+    };
+    assert_eq!(found("official")?, vec!["Island".s()]);
+    assert_eq!(found("second")?, vec!["Gremlin".s()]);
+    assert_eq!(found("first")?, vec!["Forest".s()]);
+
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use lazy_regex::regex;
+use rusqlite::{
+    types::{ToSqlOutput, Value},
+    ToSql,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::atomic_cards::types::{Pie, WUBRG};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ManaSymbol {
+    Generic(u32),
+    Variable(char),
+    Colored(Pie),
+    Colorless,
+    Snow,
+    Hybrid(Pie, Pie),
+    Phyrexian(Pie),
+    HybridPhyrexian(Pie, Pie),
+    Twobrid(Pie),
+    Other(String),
+}
+
+impl ManaSymbol {
+    pub fn colors(&self) -> Vec<Pie> {
+        match self {
+            Self::Colored(a) | Self::Phyrexian(a) | Self::Twobrid(a) => vec![*a],
+            Self::Hybrid(a, b) | Self::HybridPhyrexian(a, b) => vec![*a, *b],
+            _ => vec![],
+        }
+    }
+
+    // How much of a color the symbol demands: hybrid halves split between
+    // both colors, Phyrexian and two-brid halves can be paid some other way.
+    pub fn pip_weight(&self) -> Vec<(Pie, f64)> {
+        match self {
+            Self::Colored(a) => vec![(*a, 1.0)],
+            Self::Phyrexian(a) | Self::Twobrid(a) => vec![(*a, 0.5)],
+            Self::Hybrid(a, b) => vec![(*a, 0.5), (*b, 0.5)],
+            Self::HybridPhyrexian(a, b) => vec![(*a, 0.25), (*b, 0.25)],
+            _ => vec![],
+        }
+    }
+
+    pub fn mana_value(&self) -> f64 {
+        match self {
+            Self::Generic(n) => *n as f64,
+            Self::Variable(_) | Self::Other(_) => 0.0,
+            Self::Twobrid(_) => 2.0,
+            _ => 1.0,
+        }
+    }
+}
+
+impl From<&str> for ManaSymbol {
+    fn from(value: &str) -> Self {
+        let other = || Self::Other(value.to_owned());
+
+        let Some(inner) = value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) else {
+            return other();
+        };
+
+        let pie = |s: &str| {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Pie::try_from(c).ok(),
+                _ => None,
+            }
+        };
+
+        match inner.split('/').collect::<Vec<_>>()[..] {
+            ["C"] => Self::Colorless,
+            ["S"] => Self::Snow,
+            [v @ ("X" | "Y" | "Z")] => Self::Variable(v.chars().next().unwrap()),
+            [a] => match (pie(a), a.parse()) {
+                (Some(a), _) => Self::Colored(a),
+                (None, Ok(n)) => Self::Generic(n),
+                _ => other(),
+            },
+            ["2", a] => pie(a).map(Self::Twobrid).unwrap_or_else(other),
+            [a, "P"] => pie(a).map(Self::Phyrexian).unwrap_or_else(other),
+            [a, b] => match (pie(a), pie(b)) {
+                (Some(a), Some(b)) => Self::Hybrid(a, b),
+                _ => other(),
+            },
+            [a, b, "P"] => match (pie(a), pie(b)) {
+                (Some(a), Some(b)) => Self::HybridPhyrexian(a, b),
+                _ => other(),
+            },
+            _ => other(),
+        }
+    }
+}
+
+impl Display for ManaSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Generic(n) => write!(f, "{{{n}}}"),
+            Self::Variable(v) => write!(f, "{{{v}}}"),
+            Self::Colored(a) => write!(f, "{{{a}}}"),
+            Self::Colorless => write!(f, "{{C}}"),
+            Self::Snow => write!(f, "{{S}}"),
+            Self::Hybrid(a, b) => write!(f, "{{{a}/{b}}}"),
+            Self::Phyrexian(a) => write!(f, "{{{a}/P}}"),
+            Self::HybridPhyrexian(a, b) => write!(f, "{{{a}/{b}/P}}"),
+            Self::Twobrid(a) => write!(f, "{{2/{a}}}"),
+            Self::Other(s) => f.write_str(s),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub struct ManaCost(pub Vec<ManaSymbol>);
+
+impl ManaCost {
+    pub fn iter(&self) -> std::slice::Iter<'_, ManaSymbol> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn colors(&self) -> WUBRG {
+        WUBRG(self.iter().flat_map(ManaSymbol::colors).collect())
+    }
+
+    pub fn devotion(&self) -> BTreeMap<Pie, usize> {
+        let mut res = BTreeMap::new();
+        for pie in self.iter().flat_map(ManaSymbol::colors) {
+            *res.entry(pie).or_insert(0) += 1;
+        }
+        res
+    }
+
+    pub fn pip_weight(&self) -> BTreeMap<Pie, f64> {
+        let mut res = BTreeMap::new();
+        for (pie, weight) in self.iter().flat_map(ManaSymbol::pip_weight) {
+            *res.entry(pie).or_insert(0.0) += weight;
+        }
+        res
+    }
+
+    pub fn mana_value(&self) -> f64 {
+        self.iter().map(ManaSymbol::mana_value).sum()
+    }
+
+    pub fn has_variable(&self) -> bool {
+        self.iter().any(|s| matches!(s, ManaSymbol::Variable(_)))
+    }
+
+    pub fn contains(&self, other: &ManaCost) -> bool {
+        let mut remaining = self.0.clone();
+        other.iter().all(|symbol| {
+            if let Some(i) = remaining.iter().position(|s| s == symbol) {
+                remaining.swap_remove(i);
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    pub fn parse_shorthand(s: &str) -> ManaCost {
+        if s.contains('{') {
+            s.into()
+        } else {
+            ManaCost(
+                s.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| ManaSymbol::from(format!("{{{}}}", c.to_ascii_uppercase()).as_str()))
+                    .collect(),
+            )
+        }
+    }
+}
+
+impl From<&str> for ManaCost {
+    fn from(value: &str) -> Self {
+        ManaCost(
+            regex!(r"\{[^}]*\}")
+                .find_iter(value)
+                .map(|m| m.as_str().into())
+                .collect(),
+        )
+    }
+}
+
+impl From<String> for ManaCost {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<ManaCost> for String {
+    fn from(value: ManaCost) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for ManaCost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for symbol in self.iter() {
+            symbol.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl ToSql for ManaCost {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(Value::Text(self.to_string())))
+    }
+}
+
+#[test]
+fn test_mana_cost() {
+    use ManaSymbol::*;
+    use Pie::*;
+
+    let costs = [
+        "{X}{X}{2}{W}{W}",
+        "{W/U}{W/U}",
+        "{2/B}{2/B}{2/B}",
+        "{G/U/P}{1}",
+        "{1}{R/P}",
+        "{C}{S}{S}",
+        "{HW}{1000000}",
+        "",
+    ];
+    for cost in costs {
+        assert_eq!(ManaCost::from(cost).to_string(), cost);
+    }
+
+    let cost = ManaCost::from("{X}{2}{W}{W/U}{U/P}{2/B}{G/U/P}{HR}");
+    assert_eq!(
+        cost.0,
+        vec![
+            Variable('X'),
+            Generic(2),
+            Colored(W),
+            Hybrid(W, U),
+            Phyrexian(U),
+            Twobrid(B),
+            HybridPhyrexian(G, U),
+            Other("{HR}".into()),
+        ]
+    );
+    assert!(cost.has_variable());
+    assert_eq!(cost.colors(), WUBRG::from("WUBG"));
+    assert_eq!(
+        cost.devotion(),
+        BTreeMap::from_iter([(W, 2), (U, 3), (B, 1), (G, 1)])
+    );
+    assert_eq!(
+        cost.pip_weight(),
+        BTreeMap::from_iter([(W, 1.5), (U, 1.25), (B, 0.5), (G, 0.25)])
+    );
+    assert_eq!(cost.mana_value(), 8.0);
+
+    assert!(cost.contains(&ManaCost::parse_shorthand("w")));
+    assert!(cost.contains(&ManaCost::parse_shorthand("{W/U}{2}")));
+    assert!(!cost.contains(&ManaCost::parse_shorthand("WW")));
+    assert_eq!(
+        serde_json::from_str::<ManaCost>(r#""{1}{G}""#).unwrap(),
+        ManaCost(vec![Generic(1), Colored(G)])
+    );
+}
@@ -13,6 +13,7 @@ use rusqlite::{
     Connection, ToSql,
 };
 use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display};
 
 #[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct MetaData {
@@ -30,7 +31,7 @@ impl SqliteTable for MetaData {
 
     fn insert_row_stmt() -> String {
         let table_name = Self::table_name();
-        format!("INSERT OR REPLACE INTO {table_name} (rowid, date, version) VALUES (1, :date, :version);")
+        format!("INSERT OR REPLACE INTO {table_name} (rowid, date, version) VALUES (1, :date, :version) RETURNING rowid;")
     }
 
     fn select_row_stmt() -> String {
@@ -41,6 +42,37 @@ impl SqliteTable for MetaData {
     }
 }
 
+impl MetaData {
+    pub fn is_newer_than(&self, other: &MetaData) -> bool {
+        (Self::version_key(&self.version), &self.date)
+            > (Self::version_key(&other.version), &other.date)
+    }
+
+    fn version_key(version: &str) -> Vec<u64> {
+        version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|s| s.parse().ok())
+            .collect_vec()
+    }
+}
+
+#[test]
+fn test_metadata_versions() {
+    let meta = |version: &str, date: &str| MetaData {
+        date: date.s(),
+        version: version.s(),
+    };
+
+    let old = meta("5.2.2+20250601", "2025-06-01");
+    let new = meta("5.2.2+20250602", "2025-06-02");
+    let major = meta("5.10.0+20250101", "2025-01-01");
+
+    assert!(new.is_newer_than(&old));
+    assert!(!old.is_newer_than(&new));
+    assert!(!old.is_newer_than(&old));
+    assert!(major.is_newer_than(&new));
+}
+
 #[test]
 fn test_metadata() -> anyhow::Result<()> {
     let conn = Connection::open_in_memory()?;
@@ -257,7 +289,7 @@ impl SqliteTable for Legalities {
         let table_name = Self::table_name();
         let params = Self::param_names().join(", ");
         let columns = Self::column_names().join(", ");
-        format!("INSERT INTO {table_name} ({columns}) VALUES ({params}) ON CONFLICT ({columns}) DO UPDATE SET alchemy = excluded.alchemy;")
+        format!("INSERT INTO {table_name} ({columns}) VALUES ({params}) ON CONFLICT ({columns}) DO UPDATE SET alchemy = excluded.alchemy RETURNING rowid;")
     }
 
     fn create_extras() -> Vec<String> {
@@ -304,6 +336,12 @@ fn test_legalities() -> anyhow::Result<()> {
         Ok(())
     })?;
 
+    let mut other = 0;
+    Legalities::store_rows(&conn, |mut s| {
+        other = s.store(&Legalities::default(), &mut ())?;
+        Ok(())
+    })?;
+
     let mut id2 = 0;
     Legalities::store_rows(&conn, |mut s| {
         id2 = s.store(&data, &mut ())?;
@@ -311,10 +349,42 @@ fn test_legalities() -> anyhow::Result<()> {
     })?;
 
     assert_eq!(id, id2);
+    assert_ne!(id, other);
     Ok(())
 }
 
 impl Legalities {
+    pub fn formats(&self) -> [(&'static str, Legality); 19] {
+        [
+            ("alchemy", self.alchemy),
+            ("brawl", self.brawl),
+            ("commander", self.commander),
+            ("duel", self.duel),
+            ("explorer", self.explorer),
+            ("future", self.future),
+            ("gladiator", self.gladiator),
+            ("historic", self.historic),
+            ("historicbrawl", self.historicbrawl),
+            ("legacy", self.legacy),
+            ("modern", self.modern),
+            ("oldschool", self.oldschool),
+            ("pauper", self.pauper),
+            ("penny", self.penny),
+            ("pioneer", self.pioneer),
+            ("predh", self.predh),
+            ("premodern", self.premodern),
+            ("standard", self.standard),
+            ("vintage", self.vintage),
+        ]
+    }
+
+    pub fn format(&self, format: &str) -> Option<Legality> {
+        self.formats()
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(format))
+            .map(|(_, legality)| legality)
+    }
+
     #[allow(unused)]
     fn join(&self, other: &Legalities) -> Legalities {
         Legalities {
@@ -354,6 +424,12 @@ pub enum Legality {
     NotLegal = 0,
 }
 
+impl Display for Legality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
 impl ToSql for Legality {
     fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
         Ok(ToSqlOutput::Owned(Value::Integer(*self as i8 as i64)))
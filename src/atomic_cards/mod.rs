@@ -1,97 +1,36 @@
 pub mod cardoids;
 pub mod cards;
+pub mod diff;
 pub mod download;
+pub mod fulltext;
+pub mod homebrew;
+pub mod mana;
 pub mod metadata;
+pub mod names;
+pub mod schema;
+pub mod snapshot;
+pub mod source;
 pub mod sqlite;
 pub mod types;
 
-use std::{collections::BTreeSet, error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, path::Path};
 
-use indexmap::{IndexMap, IndexSet};
-use itertools::Itertools;
-use rusqlite::Connection;
+use indexmap::IndexMap;
+use rusqlite::{types::ValueRef, Connection, OpenFlags};
 use serde::{Deserialize, Serialize};
 
-use crate::atomic_cards::{
-    cardoids::{Cardoid, Cardoid_Keys},
-    sqlite::SqliteTableImpl,
+use crate::{
+    atomic_cards::{cardoids::Cardoid, source::CardSource},
+    query,
 };
 
-use anyhow::anyhow;
-
-#[derive(Debug, Default)]
-pub struct AtomicCards {
-    db: Option<AtomicCardsDb>,
-    file: Option<AtomicCardsFile>,
-}
-
-impl AtomicCards {
-    pub fn lookup(&self, cardname: &str) -> Option<Cardoid> {
-        if let Some(_db) = &self.db {
-            todo!();
-        } else if let Some(file) = &self.file {
-            file.data.get(cardname).cloned()
-        } else {
-            None
-        }
-    }
-
-    #[allow(unused)]
-    pub fn load_db(&mut self, _verbose: bool) -> anyhow::Result<&mut Self> {
-        Err(anyhow!("Unimplemented"))
-    }
-
-    #[allow(unused)]
-    pub fn load_json(&mut self, verbose: bool) -> anyhow::Result<&mut Self> {
-        self.file = Some(AtomicCardsFile::load_json(verbose)?);
-        Ok(self)
-    }
-
-    #[allow(unused)]
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn card_names(&self) -> anyhow::Result<Vec<String>> {
-        Ok(if let Some(db) = &self.db {
-            let mut res = vec![];
-            Cardoid::load_all(&db.conn, |_, _, Cardoid_Keys { card_name }| {
-                Ok(res.push(card_name))
-            })?;
-            res
-        } else if let Some(file) = &self.file {
-            file.data.keys().cloned().collect_vec()
-        } else {
-            vec![]
-        })
-    }
-
-    #[allow(unused)]
-    pub fn validate(&self) -> anyhow::Result<()> {
-        let mut malformed_cards = IndexSet::new();
-
-        for name in self.card_names()? {
-            let Some(cardoid) = self.lookup(&name) else {
-                malformed_cards.insert(name);
-                continue;
-            };
-
-            if cardoid.sides().len() < 1 || !cardoid.sides().is_sorted() {
-                malformed_cards.insert(name.clone());
-            }
-
-            let layouts = BTreeSet::from_iter(cardoid.iter().map(|c| c.layout.clone()));
-
-            if layouts.len() > 1 {
-                malformed_cards.insert(name.clone());
-            }
-        }
-
-        if malformed_cards.is_empty() {
-            Ok(())
-        } else {
-            Err(AtomicCardsError(malformed_cards.into_iter().collect_vec()).into())
-        }
+pub fn open(path: &Path) -> anyhow::Result<Box<dyn CardSource>> {
+    if path.extension() == Some("sqlite".as_ref()) {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Ok(Box::new(AtomicCardsDb { conn }))
+    } else {
+        let file: AtomicCardsFile = serde_json::from_slice(&std::fs::read(path)?)?;
+        Ok(Box::new(file))
     }
 }
 
@@ -101,7 +40,41 @@ pub struct AtomicCardsDb {
 }
 
 impl AtomicCardsDb {
-    const ATOMIC_CARDS_DB: &'static str = "AtomicCards.sqlite";
+    pub const ATOMIC_CARDS_DB: &'static str = "AtomicCards.sqlite";
+
+    // Ad-hoc queries for the sql command, as column names and rows of text.
+    pub fn query_table(&self, sql: &str) -> anyhow::Result<(Vec<String>, Vec<Vec<String>>)> {
+        query::sql::register_functions(&self.conn)?;
+        let mut stmt = self.conn.prepare(sql)?;
+        if !stmt.readonly() {
+            anyhow::bail!("Only statements that don't modify the database are allowed");
+        }
+
+        let columns = stmt.column_names().into_iter().map(String::from).collect();
+        let count = stmt.column_count();
+        let mut rows = vec![];
+        let mut query = stmt.query([])?;
+        while let Some(row) = query.next()? {
+            let mut cells = vec![];
+            for i in 0..count {
+                cells.push(match row.get_ref(i)? {
+                    ValueRef::Null => String::new(),
+                    ValueRef::Integer(n) => n.to_string(),
+                    ValueRef::Real(n) => n.to_string(),
+                    ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+                    ValueRef::Blob(blob) => format!(
+                        "x'{}'",
+                        blob.iter()
+                            .map(|b| format!("{:02x}", b))
+                            .collect::<String>()
+                    ),
+                });
+            }
+            rows.push(cells);
+        }
+
+        Ok((columns, rows))
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
@@ -113,7 +86,9 @@ pub struct AtomicCardsFile {
 impl AtomicCardsFile {
     const ATOMIC_CARDS_DUMP: &'static str = "AtomicCards.dump.json";
     const ATOMIC_CARDS_FILE: &'static str = "AtomicCards.json";
-    const ATOMIC_CARDS_URL: &'static str = "https://mtgjson.com/api/v5/AtomicCards.json";
+    pub const ATOMIC_CARDS_URL: &'static str = "https://mtgjson.com/api/v5/AtomicCards.json";
+    pub const MTGJSON_URL: &'static str = "https://mtgjson.com/api/v5";
+    const META_FILE: &'static str = "Meta.json";
 }
 
 #[derive(Debug)]
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+
+use crate::atomic_cards::source::CardSource;
+
+pub fn normalize(name: &str) -> String {
+    let mut res = String::with_capacity(name.len());
+
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => res.push('a'),
+            'æ' => res.push_str("ae"),
+            'ç' => res.push('c'),
+            'è' | 'é' | 'ê' | 'ë' => res.push('e'),
+            'ì' | 'í' | 'î' | 'ï' => res.push('i'),
+            'ñ' => res.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => res.push('o'),
+            'œ' => res.push_str("oe"),
+            'ù' | 'ú' | 'û' | 'ü' => res.push('u'),
+            'ý' | 'ÿ' => res.push('y'),
+            c if c.is_alphanumeric() => res.push(c),
+            // Urza's and Urzas are the same name.
+            '\'' | '’' => {}
+            _ if !res.is_empty() && !res.ends_with(' ') => res.push(' '),
+            _ => {}
+        }
+    }
+
+    res.truncate(res.trim_end().len());
+    res
+}
+
+#[derive(Debug, Default)]
+pub struct NameIndex {
+    names: BTreeMap<String, String>,
+    faces: BTreeMap<String, Vec<String>>,
+    foreign: BTreeMap<String, Vec<String>>,
+}
+
+impl NameIndex {
+    const MAX_SUGGESTIONS: usize = 3;
+
+    pub fn new<S: AsRef<str>>(names: impl IntoIterator<Item = S>) -> Self {
+        let mut res = Self::default();
+
+        for name in names {
+            let name = name.as_ref();
+            res.names.entry(normalize(name)).or_insert(name.to_owned());
+
+            if name.contains(" // ") {
+                for face in name.split(" // ") {
+                    res.faces
+                        .entry(normalize(face))
+                        .or_default()
+                        .push(name.to_owned());
+                }
+            }
+        }
+
+        res
+    }
+
+    pub fn add_foreign(&mut self, names: impl IntoIterator<Item = (String, String)>) {
+        for (name, foreign) in names {
+            let entry = self.foreign.entry(normalize(&foreign)).or_default();
+            if !entry.contains(&name) {
+                entry.push(name);
+            }
+        }
+    }
+
+    pub fn load(atomics: &dyn CardSource) -> anyhow::Result<Self> {
+        let mut res = Self::new(atomics.card_names()?);
+        res.add_foreign(atomics.foreign_names()?);
+        Ok(res)
+    }
+
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        let key = normalize(name);
+
+        if let Some(res) = self.names.get(&key) {
+            return Some(res);
+        }
+
+        if let Some([res]) = self.faces.get(&key).map(Vec::as_slice) {
+            return Some(res);
+        }
+
+        match self.foreign.get(&key).map(Vec::as_slice) {
+            Some([res]) => Some(res),
+            _ => None,
+        }
+    }
+
+    pub fn suggest(&self, name: &str) -> Vec<&str> {
+        let key = normalize(name);
+        let max_distance = (key.chars().count() / 3).max(2);
+
+        let candidates = self
+            .names
+            .iter()
+            .map(|(k, v)| (k, std::slice::from_ref(v)))
+            .chain(self.faces.iter().map(|(k, v)| (k, v.as_slice())))
+            .chain(self.foreign.iter().map(|(k, v)| (k, v.as_slice())));
+
+        candidates
+            .map(|(k, v)| (strsim::levenshtein(&key, k), v))
+            .filter(|(d, _)| *d <= max_distance)
+            .sorted_by_key(|(d, _)| *d)
+            .flat_map(|(_, v)| v.iter().map(String::as_str))
+            .unique()
+            .take(Self::MAX_SUGGESTIONS)
+            .collect()
+    }
+}
+
+#[test]
+fn test_name_index() {
+    assert_eq!(normalize("Lórien Revealed"), "lorien revealed");
+    assert_eq!(normalize("Æther Vial"), "aether vial");
+    assert_eq!(normalize("Gaea's Cradle"), "gaeas cradle");
+    assert_eq!(normalize("Urza’s Saga"), "urzas saga");
+    assert_eq!(normalize("  Fire // Ice "), "fire ice");
+
+    let mut index = NameIndex::new([
+        "Forest",
+        "Fire // Ice",
+        "Lórien Revealed",
+        "Æther Vial",
+        "Ice Age",
+        "Illusion // Reality",
+        "Reality // Illusion",
+        "Urza's Saga",
+    ]);
+    index.add_foreign([
+        ("Forest".into(), "Wald".into()),
+        ("Forest".into(), "森".into()),
+        ("Fire // Ice".into(), "Feuer // Eis".into()),
+        ("Fire // Ice".into(), "Feuer".into()),
+        ("Fire // Ice".into(), "Eis".into()),
+        ("Ice Age".into(), "Eis".into()),
+    ]);
+
+    assert_eq!(index.resolve("forest"), Some("Forest"));
+    assert_eq!(index.resolve("Lorien Revealed"), Some("Lórien Revealed"));
+    assert_eq!(index.resolve("Aether Vial"), Some("Æther Vial"));
+    assert_eq!(index.resolve("Fire"), Some("Fire // Ice"));
+    assert_eq!(index.resolve("Fire//Ice"), Some("Fire // Ice"));
+    assert_eq!(index.resolve("Illusion"), None);
+    assert_eq!(index.resolve("Forrest"), None);
+    assert_eq!(index.resolve("wald"), Some("Forest"));
+    assert_eq!(index.resolve("森"), Some("Forest"));
+    assert_eq!(index.resolve("Feuer"), Some("Fire // Ice"));
+    assert_eq!(index.resolve("Eis"), None);
+    assert_eq!(index.resolve("Urzas Saga"), Some("Urza's Saga"));
+
+    assert_eq!(index.suggest("Forrest"), vec!["Forest"]);
+    assert_eq!(index.suggest("Walt"), vec!["Forest"]);
+    assert_eq!(index.suggest("Aether Vail"), vec!["Æther Vial"]);
+    assert_eq!(
+        index.suggest("Ilusion"),
+        vec!["Illusion // Reality", "Reality // Illusion"]
+    );
+    assert!(index.suggest("Llanowar Elves").is_empty());
+}
@@ -0,0 +1,191 @@
+use rand::seq::IndexedRandom;
+use rusqlite::{Connection, ToSql};
+use sha2::{Digest, Sha256};
+
+use crate::atomic_cards::{
+    cardoids::Cardoid,
+    cards::Card,
+    fulltext,
+    metadata::{ForeignData, Legalities, MetaData},
+    source::CardSource,
+    sqlite::{db_column, DbColumn, SqliteTable, SqliteTableImpl},
+    AtomicCardsDb, AtomicCardsFile,
+};
+
+// The version is a fingerprint of every table definition, so changing
+// COLUMNS anywhere gets noticed without remembering to bump anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub version: String,
+}
+
+impl SqliteTable for Schema {
+    type Keys = ();
+
+    const COLUMNS: &'static [DbColumn<Schema, ()>] =
+        &[db_column!(object.version "TEXT NOT NULL", val.as_str())];
+}
+
+impl Schema {
+    // Only for changes the table definitions don't show, like how a column
+    // is encoded.
+    const REVISION: u32 = 1;
+
+    pub fn current() -> Schema {
+        let scripts = [
+            MetaData::full_setup_script(),
+            Schema::full_setup_script(),
+            Cardoid::full_setup_script(),
+            Card::full_setup_script(),
+            ForeignData::full_setup_script(),
+            Legalities::full_setup_script(),
+            vec![fulltext::create_table_stmt()],
+        ]
+        .concat();
+
+        let fingerprint = Sha256::digest(scripts.join("\n"))
+            .iter()
+            .take(8)
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+
+        Schema {
+            version: format!("{}-{}", Self::REVISION, fingerprint),
+        }
+    }
+
+    // None for databases built before the schema was stored.
+    pub fn installed(conn: &Connection) -> anyhow::Result<Option<Schema>> {
+        let exists: bool = conn.query_row(
+            "SELECT count(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1;",
+            [Self::table_name()],
+            |r| r.get(0),
+        )?;
+        if !exists {
+            return Ok(None);
+        }
+
+        let mut res = None;
+        Self::load_all(conn, |_, schema, _| {
+            res = Some(schema);
+            Ok(())
+        })?;
+        Ok(res)
+    }
+}
+
+impl AtomicCardsDb {
+    pub fn is_current(&self) -> anyhow::Result<bool> {
+        Ok(Schema::installed(&self.conn)? == Some(Schema::current()))
+    }
+
+    // Problems found, an empty list when the database is healthy.
+    pub fn check(&self, file: &AtomicCardsFile, sample: usize) -> anyhow::Result<Vec<String>> {
+        let mut problems = vec![];
+
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check;")?;
+        let integrity = stmt
+            .query_map([], |r| r.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        if integrity != ["ok"] {
+            problems.extend(integrity.into_iter().map(|e| format!("Integrity: {}", e)));
+        }
+
+        match Schema::installed(&self.conn)? {
+            Some(schema) if schema == Schema::current() => {}
+            Some(schema) => problems.push(format!(
+                "Schema version {} doesn't match {}",
+                schema.version,
+                Schema::current().version
+            )),
+            None => problems.push("No schema version stored".to_owned()),
+        }
+        if !problems.is_empty() {
+            // The cards can't be read reliably from a broken database.
+            return Ok(problems);
+        }
+
+        if self.meta()? != file.meta {
+            problems.push("Metadata differs from the JSON".to_owned());
+        }
+        let count = self.card_count()?;
+        if count != file.data.len() {
+            problems.push(format!(
+                "{} cards in the database, {} in the JSON",
+                count,
+                file.data.len()
+            ));
+        }
+
+        let names = file.data.keys().collect::<Vec<_>>();
+        for name in names.choose_multiple(&mut rand::rng(), sample) {
+            match self.lookup(name)? {
+                None => problems.push(format!("{} is missing", name)),
+                Some(cardoid) if Some(&cardoid) != file.data.get(*name) => {
+                    problems.push(format!("{} differs from the JSON", name))
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(problems)
+    }
+}
+
+#[test]
+fn test_schema() -> anyhow::Result<()> {
+    use crate::utils::ToS;
+
+    let mut file = AtomicCardsFile::default();
+    file.meta.version = "5.2.2".s();
+    for name in ["Foo", "Bar", "Baz"] {
+        file.data.insert(
+            name.s(),
+            Cardoid::from(vec![Card {
+                name: name.s(),
+                ..Default::default()
+            }]),
+        );
+    }
+
+    let db = AtomicCardsDb {
+        conn: Connection::open_in_memory()?,
+    };
+    assert_eq!(Schema::installed(&db.conn)?, None);
+
+    db.initialize(&file, false)?;
+    assert!(db.is_current()?);
+    assert_eq!(db.check(&file, 10)?, Vec::<String>::new());
+
+    file.data.insert(
+        "Bar".s(),
+        Cardoid::from(vec![Card {
+            name: "Bar".s(),
+            text: "Changed".s(),
+            ..Default::default()
+        }]),
+    );
+    file.data.shift_remove("Baz");
+    assert_eq!(
+        db.check(&file, 10)?,
+        vec![
+            "3 cards in the database, 2 in the JSON",
+            "Bar differs from the JSON"
+        ]
+    );
+
+    db.conn.execute(
+        &format!("UPDATE {} SET version = '0';", Schema::table_name()),
+        [],
+    )?;
+    assert!(!db.is_current()?);
+    assert_eq!(
+        db.check(&file, 10)?,
+        vec![format!(
+            "Schema version 0 doesn't match {}",
+            Schema::current().version
+        )]
+    );
+
+    Ok(())
+}
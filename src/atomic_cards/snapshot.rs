@@ -0,0 +1,420 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+};
+
+use anyhow::anyhow;
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+
+use crate::atomic_cards::{
+    cardoids::Cardoid,
+    cards::Card,
+    mana::ManaCost,
+    metadata::{ForeignData, Legalities, MetaData},
+    types::{CardLayout, LeadershipSkills, Side, Supertype, Type, WUBRG},
+    AtomicCardsFile,
+};
+
+// AtomicCards.json as bincode, with every distinct string stored once and
+// referred to by index. Reading it skips JSON parsing and most of the
+// allocations. It belongs to the JSON file next to it and is ignored once
+// that has a different version or size.
+const FORMAT: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Header {
+    format: u32,
+    version: String,
+    json_len: u64,
+}
+
+// Borrowed straight from the file's bytes when reading, so each string is
+// copied once, into the card that uses it.
+#[derive(Serialize, Deserialize)]
+struct Body<'a> {
+    meta: MetaData,
+    #[serde(borrow)]
+    strings: Vec<&'a str>,
+    #[serde(borrow)]
+    legalities: Vec<&'a str>,
+    cardoids: Vec<(u32, Vec<CardRecord>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CardRecord {
+    color_identity: WUBRG,
+    color_indicator: WUBRG,
+    colors: WUBRG,
+    defense: u32,
+    face_mana_value: Option<f64>,
+    face_name: u32,
+    // face_name, flavor_text, language, name, text, type_line
+    foreign_data: Vec<[u32; 6]>,
+    has_alternative_deck_limit: bool,
+    keywords: Vec<u32>,
+    layout: CardLayout,
+    leadership_skills: [u8; 3],
+    legalities: u32,
+    loyalty: u32,
+    mana_cost: u32,
+    mana_value: f64,
+    name: u32,
+    power: u32,
+    side: Side,
+    subtypes: Vec<u32>,
+    supertypes: Vec<Supertype>,
+    text: u32,
+    toughness: u32,
+    type_line: u32,
+    types: Vec<Type>,
+}
+
+#[derive(Default)]
+struct Interner {
+    strings: IndexSet<String>,
+    // Legalities skip their default fields, which bincode can't read back.
+    // There are only a few hundred combinations, so they stay JSON.
+    legalities: IndexSet<String>,
+}
+
+impl Interner {
+    fn string(&mut self, s: &str) -> u32 {
+        let id = match self.strings.get_index_of(s) {
+            Some(id) => id,
+            None => self.strings.insert_full(s.to_owned()).0,
+        };
+        id as u32
+    }
+
+    fn strings<'a>(&mut self, strings: impl IntoIterator<Item = &'a String>) -> Vec<u32> {
+        strings.into_iter().map(|s| self.string(s)).collect()
+    }
+
+    fn card(&mut self, card: &Card) -> anyhow::Result<CardRecord> {
+        let skills = card.leadership_skills;
+        let legalities = serde_json::to_string(&card.legalities)?;
+
+        Ok(CardRecord {
+            color_identity: card.color_identity.clone(),
+            color_indicator: card.color_indicator.clone(),
+            colors: card.colors.clone(),
+            defense: self.string(&card.defense),
+            face_mana_value: card.face_mana_value,
+            face_name: self.string(&card.face_name),
+            foreign_data: card
+                .foreign_data
+                .iter()
+                .map(|f| {
+                    [
+                        self.string(&f.face_name),
+                        self.string(&f.flavor_text),
+                        self.string(&f.language),
+                        self.string(&f.name),
+                        self.string(&f.text),
+                        self.string(&f.type_line),
+                    ]
+                })
+                .collect(),
+            has_alternative_deck_limit: card.has_alternative_deck_limit,
+            keywords: self.strings(&card.keywords),
+            layout: card.layout,
+            leadership_skills: [
+                skills.brawl as u8,
+                skills.commander as u8,
+                skills.oathbreaker as u8,
+            ],
+            legalities: self.legalities.insert_full(legalities).0 as u32,
+            loyalty: self.string(&card.loyalty),
+            mana_cost: self.string(&card.mana_cost.to_string()),
+            mana_value: card.mana_value,
+            name: self.string(&card.name),
+            power: self.string(&card.power),
+            side: card.side,
+            subtypes: self.strings(&card.subtypes),
+            supertypes: card.supertypes.clone(),
+            text: self.string(&card.text),
+            toughness: self.string(&card.toughness),
+            type_line: self.string(&card.type_line),
+            types: card.types.clone(),
+        })
+    }
+}
+
+struct Strings<'a> {
+    strings: Vec<&'a str>,
+    legalities: Vec<Legalities>,
+}
+
+impl Strings<'_> {
+    fn get(&self, id: u32) -> anyhow::Result<String> {
+        self.strings
+            .get(id as usize)
+            .map(|s| s.to_string())
+            .ok_or_else(|| anyhow!("String {} out of range", id))
+    }
+
+    fn card(&self, record: CardRecord) -> anyhow::Result<Card> {
+        Ok(Card {
+            color_identity: record.color_identity,
+            color_indicator: record.color_indicator,
+            colors: record.colors,
+            defense: self.get(record.defense)?,
+            face_mana_value: record.face_mana_value,
+            face_name: self.get(record.face_name)?,
+            foreign_data: record
+                .foreign_data
+                .into_iter()
+                .map(
+                    |[face_name, flavor_text, language, name, text, type_line]| {
+                        Ok(ForeignData {
+                            face_name: self.get(face_name)?,
+                            flavor_text: self.get(flavor_text)?,
+                            language: self.get(language)?,
+                            name: self.get(name)?,
+                            text: self.get(text)?,
+                            type_line: self.get(type_line)?,
+                        })
+                    },
+                )
+                .collect::<anyhow::Result<_>>()?,
+            has_alternative_deck_limit: record.has_alternative_deck_limit,
+            keywords: record
+                .keywords
+                .into_iter()
+                .map(|id| self.get(id))
+                .collect::<anyhow::Result<_>>()?,
+            layout: record.layout,
+            leadership_skills: LeadershipSkills::from(&record.leadership_skills[..]),
+            legalities: self
+                .legalities
+                .get(record.legalities as usize)
+                .cloned()
+                .ok_or_else(|| anyhow!("Legalities {} out of range", record.legalities))?,
+            loyalty: self.get(record.loyalty)?,
+            mana_cost: ManaCost::from(self.get(record.mana_cost)?),
+            mana_value: record.mana_value,
+            name: self.get(record.name)?,
+            power: self.get(record.power)?,
+            side: record.side,
+            subtypes: record
+                .subtypes
+                .into_iter()
+                .map(|id| self.get(id))
+                .collect::<anyhow::Result<_>>()?,
+            supertypes: record.supertypes,
+            text: self.get(record.text)?,
+            toughness: self.get(record.toughness)?,
+            type_line: self.get(record.type_line)?,
+            types: record.types,
+        })
+    }
+}
+
+impl AtomicCardsFile {
+    const SNAPSHOT_FILE: &'static str = "AtomicCards.bin";
+
+    pub fn save_snapshot(&self, dir: &Path) -> anyhow::Result<()> {
+        let json = dir.join(Self::ATOMIC_CARDS_FILE);
+        let header = Header {
+            format: FORMAT,
+            version: self.meta.version.clone(),
+            json_len: std::fs::metadata(&json)?.len(),
+        };
+
+        let mut interner = Interner::default();
+        let mut cardoids = Vec::with_capacity(self.data.len());
+        for (name, cardoid) in &self.data {
+            let cards = cardoid
+                .iter()
+                .map(|c| interner.card(c))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            cardoids.push((interner.string(name), cards));
+        }
+
+        // Written next to the real file and renamed, so a reader never sees
+        // half a snapshot.
+        let path = dir.join(Self::SNAPSHOT_FILE);
+        let partial = path.with_extension("bin.partial");
+        {
+            let mut writer = BufWriter::new(File::create(&partial)?);
+            bincode::serialize_into(&mut writer, &header)?;
+            let body = Body {
+                meta: self.meta.clone(),
+                strings: interner.strings.iter().map(|s| s.as_str()).collect(),
+                legalities: interner.legalities.iter().map(|s| s.as_str()).collect(),
+                cardoids,
+            };
+            bincode::serialize_into(&mut writer, &body)?;
+            writer.flush()?;
+        }
+        std::fs::rename(partial, path)?;
+
+        Ok(())
+    }
+
+    // The snapshot is only a cache, the cards are there either way.
+    pub fn try_save_snapshot(&self, dir: &Path) {
+        if let Err(e) = self.save_snapshot(dir) {
+            eprintln!("Couldn't write the card snapshot: {}", e);
+        }
+    }
+
+    // None when there's no snapshot or it's out of date.
+    pub fn load_snapshot(dir: &Path) -> anyhow::Result<Option<Self>> {
+        let path = dir.join(Self::SNAPSHOT_FILE);
+        let json = dir.join(Self::ATOMIC_CARDS_FILE);
+        if !std::fs::exists(&path)? || !std::fs::exists(&json)? {
+            return Ok(None);
+        }
+
+        let data = std::fs::read(&path)?;
+        let header: Header = bincode::deserialize(&data)?;
+        let expected = Header {
+            format: FORMAT,
+            version: json_version(&json)?,
+            json_len: std::fs::metadata(&json)?.len(),
+        };
+        if header != expected {
+            return Ok(None);
+        }
+
+        let body: Body =
+            bincode::deserialize(&data[bincode::serialized_size(&header)? as usize..])?;
+        let strings = Strings {
+            strings: body.strings,
+            legalities: body
+                .legalities
+                .into_iter()
+                .map(serde_json::from_str)
+                .collect::<Result<_, _>>()?,
+        };
+
+        let mut cardoids = IndexMap::with_capacity(body.cardoids.len());
+        for (name, cards) in body.cardoids {
+            let cards = cards
+                .into_iter()
+                .map(|c| strings.card(c))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            cardoids.insert(strings.get(name)?, Cardoid::from(cards));
+        }
+
+        Ok(Some(AtomicCardsFile {
+            meta: body.meta,
+            data: cardoids,
+        }))
+    }
+}
+
+// The meta object comes first in the file, so the version can be read without
+// parsing the cards.
+fn json_version(json: &Path) -> anyhow::Result<String> {
+    let mut head = vec![0; 4096];
+    let len = File::open(json)?.read(&mut head)?;
+    head.truncate(len);
+
+    let start = lazy_regex::bytes_regex!(r#"^\s*\{\s*"meta"\s*:"#)
+        .find(&head)
+        .ok_or_else(|| anyhow!("{} doesn't start with its metadata", json.display()))?
+        .end();
+    let meta = serde_json::Deserializer::from_slice(&head[start..])
+        .into_iter::<MetaData>()
+        .next()
+        .ok_or_else(|| anyhow!("{} has no metadata", json.display()))??;
+
+    Ok(meta.version)
+}
+
+#[test]
+fn test_snapshot() -> anyhow::Result<()> {
+    use crate::atomic_cards::{metadata::Legality, source::CardSource, AtomicCardsDb};
+    use crate::utils::ToS;
+
+    let mut file = AtomicCardsFile::default();
+    file.meta.version = "5.2.2+20250601".s();
+    file.meta.date = "2025-06-01".s();
+    let card = |name: &str, side| Card {
+        name: name.s(),
+        face_name: if side == Side::B { "Ice" } else { "Fire" }.s(),
+        side,
+        mana_cost: ManaCost::from("{1}{R}"),
+        mana_value: 4.0,
+        face_mana_value: Some(2.0),
+        colors: WUBRG::from("UR"),
+        color_identity: WUBRG::from("UR"),
+        type_line: "Instant".s(),
+        types: vec![Type::Instant],
+        supertypes: vec![Supertype::Legendary],
+        subtypes: vec!["Elf".s(), "Druid".s()],
+        keywords: ["Flying".s(), "Reach".s()].into(),
+        layout: CardLayout::Split,
+        leadership_skills: LeadershipSkills {
+            commander: true,
+            ..Default::default()
+        },
+        legalities: Legalities {
+            vintage: Legality::Restricted,
+            legacy: Legality::Legal,
+            ..Default::default()
+        },
+        foreign_data: vec![ForeignData {
+            language: "German".s(),
+            name: "Feuer // Eis".s(),
+            text: "Feuer verursacht 2 Schadenspunkte".s(),
+            ..Default::default()
+        }],
+        text: "Fire deals 2 damage".s(),
+        ..Default::default()
+    };
+    file.data.insert(
+        "Fire // Ice".s(),
+        Cardoid::from(vec![
+            card("Fire // Ice", Side::A),
+            card("Fire // Ice", Side::B),
+        ]),
+    );
+    file.data.insert(
+        "Llanowar Elves".s(),
+        Cardoid::from(vec![Card {
+            name: "Llanowar Elves".s(),
+            power: "1".s(),
+            toughness: "1".s(),
+            ..Default::default()
+        }]),
+    );
+
+    let dir = std::env::temp_dir().join(format!("proxygenitus-snapshot-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    assert!(AtomicCardsFile::load_snapshot(&dir)?.is_none());
+
+    file.save_json(&dir)?;
+    file.save_snapshot(&dir)?;
+    let loaded = AtomicCardsFile::load_snapshot(&dir)?.expect("snapshot is current");
+    assert_eq!(loaded.meta, file.meta);
+    assert_eq!(loaded.data, file.data);
+
+    // A new version of the JSON makes the snapshot stale.
+    file.meta.version = "5.2.2+20250701".s();
+    file.save_json(&dir)?;
+    assert!(AtomicCardsFile::load_snapshot(&dir)?.is_none());
+    assert_eq!(AtomicCardsFile::load_json(&dir, false)?.meta, file.meta);
+    assert!(AtomicCardsFile::load_snapshot(&dir)?.is_some());
+
+    // The database is built from the snapshot when it's missing.
+    let db = AtomicCardsDb::load_or_build(&dir, false)?;
+    assert_eq!(db.meta()?, file.meta);
+    assert_eq!(
+        db.lookup("Fire // Ice")?.as_ref(),
+        file.data.get("Fire // Ice")
+    );
+
+    // A snapshot that can't be written doesn't stop the cards from loading.
+    std::fs::remove_file(dir.join(AtomicCardsFile::SNAPSHOT_FILE))?;
+    std::fs::create_dir(dir.join(AtomicCardsFile::SNAPSHOT_FILE))?;
+    assert_eq!(AtomicCardsFile::load_json(&dir, false)?.data, file.data);
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use std::collections::BTreeSet;
+
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use rusqlite::{params_from_iter, Connection};
+
+use crate::{
+    atomic_cards::{
+        cardoids::{Cardoid, Cardoid_Keys},
+        cards::Card,
+        fulltext,
+        metadata::{ForeignData, MetaData},
+        sqlite::{SqliteTable, SqliteTableImpl},
+        AtomicCardsDb, AtomicCardsError, AtomicCardsFile,
+    },
+    query::{sql, Query},
+};
+
+pub trait CardSource {
+    fn lookup(&self, cardname: &str) -> anyhow::Result<Option<Cardoid>>;
+
+    fn for_each_cardoid(
+        &self,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()>;
+
+    // The cardoids found among the names, keyed by name.
+    fn lookup_many(&self, cardnames: &[&str]) -> anyhow::Result<IndexMap<String, Cardoid>> {
+        let mut res = IndexMap::new();
+        for name in cardnames {
+            if let Some(cardoid) = self.lookup(name)? {
+                res.insert(name.to_string(), cardoid);
+            }
+        }
+        Ok(res)
+    }
+
+    fn meta(&self) -> anyhow::Result<MetaData>;
+
+    // Visits at least the cardoids matching the query, the mapper still has
+    // to check each one.
+    fn for_each_matching(
+        &self,
+        _query: &Query,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.for_each_cardoid(mapper)
+    }
+
+    // Sources without a database index their cards in memory on every call.
+    fn text_search(&self, query: &str) -> anyhow::Result<Vec<(String, f64)>> {
+        let conn = Connection::open_in_memory()?;
+        fulltext::build(&conn, |f| self.for_each_cardoid(f))?;
+        fulltext::search(&conn, query)
+    }
+
+    fn card_names(&self) -> anyhow::Result<Vec<String>> {
+        let mut res = vec![];
+        self.for_each_cardoid(&mut |name, _| {
+            res.push(name.to_owned());
+            Ok(())
+        })?;
+        Ok(res)
+    }
+
+    fn card_count(&self) -> anyhow::Result<usize> {
+        Ok(self.card_names()?.len())
+    }
+
+    fn foreign_names(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let mut res = vec![];
+        self.for_each_cardoid(&mut |name, cardoid| {
+            res.extend(
+                cardoid
+                    .foreign_names()
+                    .map(|n| (name.to_owned(), n.to_owned())),
+            );
+            Ok(())
+        })?;
+        Ok(res)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut malformed_cards = IndexSet::new();
+
+        self.for_each_cardoid(&mut |name, cardoid| {
+            if cardoid.is_empty() || !cardoid.sides().is_sorted() {
+                malformed_cards.insert(name.to_owned());
+            }
+
+            let layouts = BTreeSet::from_iter(cardoid.iter().map(|c| c.layout));
+
+            if layouts.len() > 1 {
+                malformed_cards.insert(name.to_owned());
+            }
+
+            Ok(())
+        })?;
+
+        if malformed_cards.is_empty() {
+            Ok(())
+        } else {
+            Err(AtomicCardsError(malformed_cards.into_iter().collect_vec()).into())
+        }
+    }
+}
+
+impl CardSource for AtomicCardsFile {
+    fn lookup(&self, cardname: &str) -> anyhow::Result<Option<Cardoid>> {
+        self.data.lookup(cardname)
+    }
+
+    fn for_each_cardoid(
+        &self,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.data.for_each_cardoid(mapper)
+    }
+
+    fn meta(&self) -> anyhow::Result<MetaData> {
+        Ok(self.meta.clone())
+    }
+
+    fn card_count(&self) -> anyhow::Result<usize> {
+        Ok(self.data.len())
+    }
+}
+
+impl CardSource for AtomicCardsDb {
+    fn lookup(&self, cardname: &str) -> anyhow::Result<Option<Cardoid>> {
+        let mut res = None;
+
+        Cardoid::load_keys(
+            [&Cardoid_Keys {
+                card_name: cardname.to_owned(),
+            }],
+            &self.conn,
+            |_, c, _| {
+                res = Some(c);
+                Ok(())
+            },
+        )?;
+
+        Ok(res)
+    }
+
+    fn lookup_many(&self, cardnames: &[&str]) -> anyhow::Result<IndexMap<String, Cardoid>> {
+        let keys = cardnames
+            .iter()
+            .map(|n| Cardoid_Keys {
+                card_name: n.to_string(),
+            })
+            .collect_vec();
+
+        let mut res = IndexMap::new();
+        Cardoid::load_keys(&keys, &self.conn, |_, c, k| {
+            res.insert(k.card_name, c);
+            Ok(())
+        })?;
+        Ok(res)
+    }
+
+    fn for_each_cardoid(
+        &self,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        Cardoid::load_all(&self.conn, |_, c, k| mapper(&k.card_name, &c))
+    }
+
+    fn for_each_matching(
+        &self,
+        query: &Query,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let Some(sql) = query.to_sql() else {
+            return self.for_each_cardoid(mapper);
+        };
+
+        sql::register_functions(&self.conn)?;
+        let table_name = Cardoid::table_name();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT rowid FROM {table_name} WHERE {};",
+            sql.clause
+        ))?;
+        let ids = stmt
+            .query_map(params_from_iter(&sql.params), |r| r.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+
+        Cardoid::load_rows(ids, &self.conn, |_, c, k| mapper(&k.card_name, &c))
+    }
+
+    fn meta(&self) -> anyhow::Result<MetaData> {
+        let mut res = MetaData::default();
+        MetaData::load_rows([1], &self.conn, |_, m, _| {
+            res = m;
+            Ok(())
+        })?;
+        Ok(res)
+    }
+
+    // Databases built before the text index existed get it on first use.
+    fn text_search(&self, query: &str) -> anyhow::Result<Vec<(String, f64)>> {
+        if !fulltext::exists(&self.conn)? {
+            fulltext::build(&self.conn, |f| self.for_each_cardoid(f))?;
+        }
+        fulltext::search(&self.conn, query)
+    }
+
+    fn card_names(&self) -> anyhow::Result<Vec<String>> {
+        let table_name = Cardoid::table_name();
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT card_name FROM {table_name};"))?;
+        let res = stmt
+            .query_map([], |r| r.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(res)
+    }
+
+    fn card_count(&self) -> anyhow::Result<usize> {
+        let table_name = Cardoid::table_name();
+        Ok(self
+            .conn
+            .query_row(&format!("SELECT count(*) FROM {table_name};"), [], |r| {
+                r.get(0)
+            })?)
+    }
+
+    fn foreign_names(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let cardoid = Cardoid::table_name();
+        let card = Card::table_name();
+        let foreign = ForeignData::table_name();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT DISTINCT {cardoid}.card_name, {foreign}.name, {foreign}.face_name FROM {foreign} \
+             JOIN {card} ON {card}.rowid = {foreign}.parent_card \
+             JOIN {cardoid} ON {cardoid}.rowid = {card}.cardoid;"
+        ))?;
+
+        let mut res = vec![];
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let card_name: String = row.get(0)?;
+            for i in [1, 2] {
+                let name: String = row.get(i)?;
+                if !name.is_empty() {
+                    res.push((card_name.clone(), name));
+                }
+            }
+        }
+        Ok(res.into_iter().unique().collect())
+    }
+}
+
+impl CardSource for IndexMap<String, Cardoid> {
+    fn lookup(&self, cardname: &str) -> anyhow::Result<Option<Cardoid>> {
+        Ok(self.get(cardname).cloned())
+    }
+
+    fn for_each_cardoid(
+        &self,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.iter().try_for_each(|(n, c)| mapper(n, c))
+    }
+
+    fn meta(&self) -> anyhow::Result<MetaData> {
+        Ok(MetaData::default())
+    }
+
+    fn card_count(&self) -> anyhow::Result<usize> {
+        Ok(self.len())
+    }
+}
+
+#[test]
+fn test_card_sources() -> anyhow::Result<()> {
+    use crate::utils::ToS;
+
+    let mut file = AtomicCardsFile::default();
+    file.meta.version = "5.2.2".s();
+    for name in ["Foo", "Bar"] {
+        file.data.insert(
+            name.s(),
+            Cardoid::from(vec![Card {
+                name: name.s(),
+                foreign_data: vec![ForeignData {
+                    language: "German".s(),
+                    name: name.to_uppercase(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }]),
+        );
+    }
+
+    let db = AtomicCardsDb {
+        conn: Connection::open_in_memory()?,
+    };
+    db.initialize(&file, false)?;
+
+    let fixtures = file.data.clone();
+
+    let sources: [&dyn CardSource; 3] = [&file, &db, &fixtures];
+    for source in sources {
+        assert_eq!(source.card_names()?, vec!["Foo".s(), "Bar".s()]);
+        assert_eq!(source.card_count()?, 2);
+        assert_eq!(source.lookup("Foo")?.as_ref(), file.data.get("Foo"));
+        assert_eq!(source.lookup("Qux")?, None);
+        assert_eq!(
+            source.lookup_many(&["Bar", "Qux", "Foo"])?,
+            IndexMap::from([
+                ("Bar".s(), file.data["Bar"].clone()),
+                ("Foo".s(), file.data["Foo"].clone())
+            ])
+        );
+        assert_eq!(
+            source.foreign_names()?.into_iter().sorted().collect_vec(),
+            vec![("Bar".s(), "BAR".s()), ("Foo".s(), "FOO".s())]
+        );
+        source.validate()?;
+        assert_eq!(
+            source
+                .text_search("foo")?
+                .into_iter()
+                .map(|(n, _)| n)
+                .collect_vec(),
+            vec!["Foo".s()]
+        );
+
+        let mut seen = vec![];
+        source.for_each_cardoid(&mut |n, c| {
+            seen.push((n.s(), c.clone()));
+            Ok(())
+        })?;
+        assert_eq!(seen, file.data.clone().into_iter().collect_vec());
+    }
+
+    assert_eq!(db.meta()?, file.meta);
+
+    Ok(())
+}
@@ -4,8 +4,9 @@ use std::{any::Any, default, fmt::Display, marker::PhantomData, time::Instant};
 
 use itertools::Itertools;
 use rusqlite::{
+    params_from_iter,
     types::{FromSql, ToSqlOutput, Value, ValueRef},
-    Connection, Params, Row, Statement, ToSql,
+    CachedStatement, Connection, Params, Row, Statement, ToSql,
 };
 
 use anyhow::anyhow;
@@ -238,7 +239,7 @@ pub trait SqliteTable: Sized + Default + 'static {
         let table_name = Self::table_name();
         let column_names = Self::column_names().join(", ");
         let params = Self::param_names().join(", ");
-        format!("INSERT INTO {table_name} ({column_names}) VALUES ({params});")
+        format!("INSERT INTO {table_name} ({column_names}) VALUES ({params}) RETURNING rowid;")
     }
 
     fn select_row_stmt() -> String {
@@ -400,7 +401,7 @@ pub trait SqliteTableImpl: SqliteTable {
     where
         F: FnMut(i64, Self, Self::Keys) -> anyhow::Result<()>,
     {
-        let mut stmt = conn.prepare(&Self::select_row_stmt())?;
+        let mut stmt = conn.prepare_cached(&Self::select_row_stmt())?;
 
         for id in ids {
             let (id, mut object, key) =
@@ -421,7 +422,7 @@ pub trait SqliteTableImpl: SqliteTable {
     where
         F: FnMut(i64, Self, Self::Keys) -> anyhow::Result<()>,
     {
-        let mut stmt = conn.prepare(&Self::select_keyed_stmt())?;
+        let mut stmt = conn.prepare_cached(&Self::select_keyed_stmt())?;
 
         for key in keys {
             let params = Self::into_key_params(key);
@@ -455,20 +456,22 @@ pub trait SqliteTableImpl: SqliteTable {
     where
         F: FnOnce(Store<'a, Self, Self::Keys>) -> anyhow::Result<()>,
     {
-        let mut stmt = conn.prepare(&Self::insert_row_stmt())?;
+        data_provider(Self::inserter(conn)?)?;
 
-        data_provider(Store {
-            stmt,
+        Ok(())
+    }
+
+    fn inserter(conn: &Connection) -> anyhow::Result<Store<'_, Self, Self::Keys>> {
+        Ok(Store {
+            stmt: conn.prepare_cached(&Self::insert_row_stmt())?,
             conn,
             _marker: PhantomData,
-        })?;
-
-        Ok(())
+        })
     }
 }
 
 pub struct Store<'a, O: SqliteTable<Keys = K>, K> {
-    stmt: Statement<'a>,
+    stmt: CachedStatement<'a>,
     conn: &'a Connection,
     _marker: PhantomData<(O, K)>,
 }
@@ -476,10 +479,21 @@ pub struct Store<'a, O: SqliteTable<Keys = K>, K> {
 impl<'a, O: SqliteTable<Keys = K>, K> Store<'a, O, K> {
     pub fn store(&mut self, obj: &O, key: &mut K) -> anyhow::Result<i64> {
         obj.pre_store(key, &self.conn)?;
-        let id = self.stmt.insert(&obj.into_params(key)?[..])?;
+        let id = self.insert(obj, key)?;
         obj.post_store(id, &self.conn)?;
         Ok(id)
     }
+
+    // Just the row, without the pre_store and post_store hooks.
+    pub fn insert(&mut self, obj: &O, key: &K) -> anyhow::Result<i64> {
+        // The statement lists its parameters in COLUMNS order, binding them by
+        // position saves looking each name up.
+        let params = obj.into_params(key)?;
+        Ok(self.stmt.query_row(
+            params_from_iter(params.iter().map(|(_, value)| value)),
+            |r| r.get("rowid"),
+        )?)
+    }
 }
 
 impl<T: SqliteTable> SqliteTableImpl for T {}
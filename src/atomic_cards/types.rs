@@ -12,15 +12,46 @@ use std::{
     ops::Sub,
 };
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Supertype {
-    Basic,
-    Legendary,
-    Snow,
+    Basic = 0,
+    Elite = 4,
+    Host = 5,
+    Legendary = 1,
+    Ongoing = 6,
+    Snow = 2,
+    World = 7,
 
     #[serde(other)]
-    Unsupported,
+    Unsupported = 3,
+}
+
+impl Supertype {
+    pub const ALL: [Supertype; 8] = [
+        Self::Basic,
+        Self::Elite,
+        Self::Host,
+        Self::Legendary,
+        Self::Ongoing,
+        Self::Snow,
+        Self::World,
+        Self::Unsupported,
+    ];
+}
+
+// Ordered like ALL, not by the stored discriminants.
+impl Ord for Supertype {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let rank = |t: &Self| Self::ALL.iter().position(|a| a == t);
+        rank(self).cmp(&rank(other))
+    }
+}
+
+impl PartialOrd for Supertype {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl Display for Supertype {
@@ -31,53 +62,97 @@ impl Display for Supertype {
 
 impl From<u8> for Supertype {
     fn from(value: u8) -> Self {
-        match value {
-            0 => Self::Basic,
-            1 => Self::Legendary,
-            2 => Self::Snow,
-            _ => Self::Unsupported,
-        }
+        Self::ALL
+            .into_iter()
+            .find(|t| *t as u8 == value)
+            .unwrap_or(Self::Unsupported)
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Type {
-    Artifact,
-    Battle,
-    Creature,
-    Enchantment,
-    Instant,
-    Kindred,
-    Land,
-    Planeswalker,
-    Sorcery,
+    Artifact = 0,
+    Battle = 1,
+    Conspiracy = 10,
+    Creature = 2,
+    Dungeon = 11,
+    Emblem = 12,
+    Enchantment = 3,
+    Hero = 13,
+    Instant = 4,
+    Kindred = 5,
+    Land = 6,
+    Phenomenon = 14,
+    Plane = 15,
+    Planeswalker = 7,
+    Scheme = 16,
+    Sorcery = 8,
+    Tribal = 17,
+    Vanguard = 18,
 
     #[serde(other)]
-    Unsupported,
-}
-
-impl From<u8> for Type {
-    fn from(value: u8) -> Self {
-        const LO: u8 = Type::Artifact as u8;
-        const HI: u8 = Type::Unsupported as u8;
-        match value {
-            LO..=HI => unsafe { std::mem::transmute(value as u8) },
-            _ => Self::Unsupported,
-        }
-    }
+    Unsupported = 9,
 }
 
 impl Type {
+    pub const ALL: [Type; 19] = [
+        Self::Artifact,
+        Self::Battle,
+        Self::Conspiracy,
+        Self::Creature,
+        Self::Dungeon,
+        Self::Emblem,
+        Self::Enchantment,
+        Self::Hero,
+        Self::Instant,
+        Self::Kindred,
+        Self::Land,
+        Self::Phenomenon,
+        Self::Plane,
+        Self::Planeswalker,
+        Self::Scheme,
+        Self::Sorcery,
+        Self::Tribal,
+        Self::Vanguard,
+        Self::Unsupported,
+    ];
+
     pub fn plural(&self) -> Option<String> {
         match self {
             Type::Sorcery => Some("Sorceries".s()),
-            Type::Kindred | Type::Unsupported => None,
+            Type::Hero => Some("Heroes".s()),
+            Type::Phenomenon => Some("Phenomena".s()),
+            Type::Kindred | Type::Tribal | Type::Unsupported => None,
             rest => Some(format!("{}s", rest)),
         }
     }
 }
 
+impl From<u8> for Type {
+    fn from(value: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|t| *t as u8 == value)
+            .unwrap_or(Self::Unsupported)
+    }
+}
+
+// The discriminants are stored in the database and can't move, so the order
+// comes from ALL: alphabetical, Unsupported last.
+impl Ord for Type {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let rank = |t: &Self| Self::ALL.iter().position(|a| a == t);
+        rank(self).cmp(&rank(other))
+    }
+}
+
+impl PartialOrd for Type {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         Debug::fmt(self, f)
@@ -334,6 +409,19 @@ pub struct LeadershipSkills {
     pub oathbreaker: bool,
 }
 
+impl LeadershipSkills {
+    pub const FORMATS: [&'static str; 3] = ["commander", "brawl", "oathbreaker"];
+
+    pub fn format(&self, format: &str) -> Option<bool> {
+        match format {
+            "commander" => Some(self.commander),
+            "brawl" => Some(self.brawl),
+            "oathbreaker" => Some(self.oathbreaker),
+            _ => None,
+        }
+    }
+}
+
 impl From<&[u8]> for LeadershipSkills {
     fn from(value: &[u8]) -> Self {
         Self {
@@ -381,3 +469,36 @@ impl From<i64> for Side {
         }
     }
 }
+
+#[test]
+fn test_type_catalog() -> anyhow::Result<()> {
+    for t in Type::ALL {
+        let json = serde_json::to_string(&t)?;
+        assert_eq!(serde_json::from_str::<Type>(&json)?, t);
+        assert_eq!(Type::from(t as u8), t);
+    }
+    for t in Supertype::ALL {
+        let json = serde_json::to_string(&t)?;
+        assert_eq!(serde_json::from_str::<Supertype>(&json)?, t);
+        assert_eq!(Supertype::from(t as u8), t);
+    }
+
+    assert_eq!(
+        serde_json::from_str::<Vec<Type>>(r#"["Plane", "Tribal", "Scheme", "Eaturecray"]"#)?,
+        vec![Type::Plane, Type::Tribal, Type::Scheme, Type::Unsupported]
+    );
+    assert_eq!(
+        serde_json::from_str::<Vec<Supertype>>(r#"["World", "Ongoing", "Elite", "Host"]"#)?,
+        vec![
+            Supertype::World,
+            Supertype::Ongoing,
+            Supertype::Elite,
+            Supertype::Host
+        ]
+    );
+    assert_eq!(Type::from(200), Type::Unsupported);
+    assert_eq!(Type::Phenomenon.plural(), Some("Phenomena".s()));
+    assert_eq!(Type::Tribal.plural(), None);
+
+    Ok(())
+}
@@ -32,7 +32,7 @@ impl Build {
         &self.decklist
     }
 
-    pub fn dispatch(&self, decklist: &mut DeckList) -> anyhow::Result<()> {
+    pub fn dispatch(&self, data_dir: &Path, decklist: &mut DeckList) -> anyhow::Result<()> {
         let settings = RenderSettings {
             in_color: self.in_color,
             testing: self.testing,
@@ -43,6 +43,7 @@ impl Build {
             } else {
                 None
             },
+            offline: false,
             scryfall: if self.force_scryfall_art {
                 eprintln!("Scryfall art will be downloaded for all cards.");
                 Some(true)
@@ -53,7 +54,7 @@ impl Build {
                 Some(false)
             },
         };
-        let mut render = RenderContext::new(settings)?;
+        let mut render = RenderContext::new(settings, data_dir)?;
 
         eprintln!(
             "Rendering {} cards",
@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use clap::{Parser, Subcommand};
+
+use crate::atomic_cards::{AtomicCardsDb, AtomicCardsFile};
+
+/// Inspect the card database
+#[derive(Parser, Debug, Clone)]
+pub struct Db {
+    #[command(subcommand)]
+    pub action: DbAction,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DbAction {
+    /// Run SQLite's integrity check and compare a random sample of cards
+    /// against AtomicCards.json
+    Check {
+        /// How many cards to compare
+        #[arg(long, default_value_t = 100, value_name = "N")]
+        sample: usize,
+    },
+}
+
+impl Db {
+    pub fn decklist_file(&self) -> &Path {
+        Path::new("")
+    }
+
+    pub fn dispatch(&self, data_dir: &Path, verbose: bool) -> anyhow::Result<()> {
+        match self.action {
+            DbAction::Check { sample } => {
+                // Not load_or_build, that would quietly replace an outdated
+                // database instead of reporting it.
+                let path = data_dir.join(AtomicCardsDb::ATOMIC_CARDS_DB);
+                if !std::fs::exists(&path)? {
+                    anyhow::bail!("{} not found, run setup first", path.display());
+                }
+                let db = AtomicCardsDb::load_sqlite(data_dir)?;
+                let file = AtomicCardsFile::load_json(data_dir, verbose)?;
+
+                let problems = db.check(&file, sample)?;
+                if problems.is_empty() {
+                    println!("{}: OK", path.display());
+                    return Ok(());
+                }
+
+                for problem in &problems {
+                    println!("{}", problem);
+                }
+                anyhow::bail!(
+                    "{} {} found in {}",
+                    problems.len(),
+                    if problems.len() == 1 {
+                        "problem"
+                    } else {
+                        "problems"
+                    },
+                    path.display()
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use indexmap::IndexSet;
+use itertools::Itertools;
+
+use crate::{
+    atomic_cards::{self, diff::Change, source::CardSource},
+    proxy::decklists::DeckListFile,
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct Diff {
+    #[arg(value_name = "OLD")]
+    pub old: PathBuf,
+    #[arg(value_name = "NEW")]
+    pub new: PathBuf,
+    #[arg(long, value_name = "FILE")]
+    pub deck: Option<PathBuf>,
+}
+
+impl Diff {
+    // The deck is resolved against the old data in dispatch, so cards renamed
+    // since then still show up as changes.
+    pub fn decklist_file(&self) -> &Path {
+        Path::new("")
+    }
+
+    pub fn dispatch(&self) -> anyhow::Result<()> {
+        let old = atomic_cards::open(&self.old)?;
+        let new = atomic_cards::open(&self.new)?;
+
+        let (old_meta, new_meta) = (old.meta()?, new.meta()?);
+        println!(
+            "Comparing version {} ({}) to {} ({})",
+            old_meta.version, old_meta.date, new_meta.version, new_meta.date
+        );
+
+        let names = match &self.deck {
+            Some(deck) => Self::deck_names(deck, old.as_ref())?,
+            None => old.card_names()?.into_iter().collect(),
+        };
+
+        let mut changed = 0usize;
+        let mut removed = vec![];
+
+        for name in names.iter().sorted() {
+            let Some(before) = old.lookup(name)? else {
+                continue;
+            };
+            let Some(after) = new.lookup(name)? else {
+                removed.push(name);
+                continue;
+            };
+
+            let changes = before.diff(&after);
+            if !changes.is_empty() {
+                changed += 1;
+                println!();
+                Self::print_changes(name, &changes);
+            }
+        }
+
+        if !removed.is_empty() {
+            println!();
+            println!("Removed:");
+            for name in &removed {
+                println!("  {name}");
+            }
+        }
+
+        if self.deck.is_none() {
+            let added = new
+                .card_names()?
+                .into_iter()
+                .filter(|n| !names.contains(n))
+                .sorted()
+                .collect_vec();
+
+            if !added.is_empty() {
+                println!();
+                println!("Added:");
+                for name in &added {
+                    println!("  {name}");
+                }
+            }
+        }
+
+        println!();
+        println!("{} changed, {} removed", changed, removed.len());
+
+        Ok(())
+    }
+
+    fn deck_names(deck: &Path, old: &dyn CardSource) -> anyhow::Result<IndexSet<String>> {
+        Ok(DeckListFile::load(deck)?
+            .resolve(old)?
+            .iter()
+            .map(|p| p.name.clone())
+            .collect())
+    }
+
+    fn print_changes(name: &str, changes: &[Change]) {
+        println!("{name}:");
+        for change in changes {
+            if change.face == name {
+                println!("  {}:", change.field);
+            } else {
+                println!("  {} {}:", change.face, change.field);
+            }
+            for line in change.old.lines() {
+                println!("    - {line}");
+            }
+            for line in change.new.lines() {
+                println!("    + {line}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_deck_names() -> anyhow::Result<()> {
+    use crate::{
+        atomic_cards::{cardoids::Cardoid, cards::Card},
+        utils::ToS,
+    };
+    use indexmap::IndexMap;
+
+    let old = IndexMap::from([(
+        "Lórien Revealed".s(),
+        Cardoid::from(vec![Card {
+            name: "Lórien Revealed".s(),
+            ..Default::default()
+        }]),
+    )]);
+
+    let dir = std::env::temp_dir().join(format!("proxygenitus-diff-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let deck = dir.join("deck.json");
+    std::fs::write(&deck, r#"{"": [{"name": "lorien revealed"}]}"#)?;
+
+    // Only the old data knows the card, the installed one may have renamed it.
+    assert_eq!(
+        Diff::deck_names(&deck, &old)?,
+        IndexSet::from(["Lórien Revealed".s()])
+    );
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
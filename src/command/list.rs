@@ -1,16 +1,18 @@
 use clap::Parser;
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use rand::{seq::SliceRandom, SeedableRng};
-use regex::Regex;
+use serde::Serialize;
 
 use std::{
     collections::BTreeMap,
+    fmt::Write,
     path::{Path, PathBuf},
 };
 
 use crate::{
     atomic_cards::types::*,
+    command::output::{self, Format},
     proxy::{decklists::DeckList, Proxy},
     utils::ToS,
 };
@@ -51,6 +53,64 @@ pub struct List {
     pub pips: bool,
     #[arg(long)]
     pub moxfield: bool,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Report {
+    Identity(String),
+    Cards(CardListing),
+    Histogram(IndexMap<String, usize>),
+    Curve(IndexMap<String, usize>),
+    Creatures(IndexMap<String, Creature>),
+    Hand(Vec<String>),
+    Lands(LandBase),
+    Moxfield(Vec<MoxfieldLine>),
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CardListing {
+    pub total: usize,
+    pub categories: IndexMap<String, IndexMap<String, usize>>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Creature {
+    pub count: usize,
+    pub pt: String,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct LandBase {
+    pub total: usize,
+    pub basic: LandGroup,
+    pub tapland: LandGroup,
+    pub nonmana: LandGroup,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct LandGroup {
+    pub count: usize,
+    pub names: IndexSet<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MoxfieldLine {
+    pub count: usize,
+    pub name: String,
+    pub tags: IndexSet<String>,
+}
+
+// Every report flattens to the same columns so CSV and NDJSON output can mix
+// reports in one stream.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReportRow<'a> {
+    pub report: &'a str,
+    pub group: String,
+    pub name: String,
+    pub count: Option<usize>,
 }
 
 impl List {
@@ -59,118 +119,135 @@ impl List {
     }
 
     pub fn dispatch(&self, decklist: &DeckList) -> anyhow::Result<()> {
-        if decklist.is_empty() {}
+        let reports = self.reports(decklist);
+
+        match self.format {
+            Format::Text => {
+                for (_, title, report) in &reports {
+                    println!();
+                    report.print(title);
+                }
+                println!();
+            }
+            Format::Json => output::print_json(
+                &reports
+                    .iter()
+                    .map(|(key, _, report)| (key, report))
+                    .collect::<IndexMap<_, _>>(),
+            )?,
+            format => output::print_records(
+                format,
+                reports.iter().flat_map(|(key, _, report)| report.rows(key)),
+            )?,
+        }
+
+        Ok(())
+    }
+
+    pub fn reports(&self, decklist: &DeckList) -> Vec<(&'static str, &'static str, Report)> {
+        let mut res = vec![];
 
         if self.id {
-            println!();
-            Self::print_color_id(decklist);
+            res.push(("id", "Color Identity", Self::color_id(decklist)));
         }
 
         if self.cards {
-            println!();
-            Self::print_cards(decklist, "Cards", Proxy::in_deck);
+            res.push(("cards", "Cards", Self::cards(decklist, Proxy::in_deck)));
         }
 
         if self.sideboard {
-            println!();
-            Self::print_cards(decklist, "Sideboard", |p| p.sideboard);
+            res.push((
+                "sideboard",
+                "Sideboard",
+                Self::cards(decklist, |p| p.sideboard),
+            ));
         }
 
         if self.tokens {
-            println!();
-            Self::print_cards(decklist, "Tokens", |p| p.layout() == &CardLayout::Token);
+            res.push((
+                "tokens",
+                "Tokens",
+                Self::cards(decklist, |p| p.layout() == &CardLayout::Token),
+            ));
         }
 
         if self.colors {
-            println!();
-            Self::print_color_hist(decklist);
+            res.push(("colors", "Color Histogram", Self::color_hist(decklist)));
         }
 
         if self.curve {
-            println!();
-            Self::print_mana_curve(decklist);
+            res.push(("curve", "Mana Curve", Self::mana_curve(decklist)));
         }
 
         if self.types {
-            println!();
-            Self::print_type_hist(decklist);
+            res.push(("types", "Card Types", Self::type_hist(decklist)));
         }
 
         if self.tags {
-            println!();
-            Self::print_tag_hist(decklist);
+            res.push(("tags", "Tags", Self::tag_hist(decklist)));
         }
 
         if self.creatures {
-            println!();
-            Self::print_creatures(decklist);
+            res.push(("creatures", "Creatures", Self::creatures(decklist)));
         }
 
         if self.creature_types {
-            println!();
-            Self::print_creature_types(decklist);
+            res.push((
+                "creature_types",
+                "Creature types",
+                Self::creature_types(decklist),
+            ));
         }
 
         if self.p_t {
-            println!();
-            Self::print_power_curve(decklist, true);
+            res.push(("p_t", "P/T curve", Self::power_curve(decklist, true)));
         }
 
         if self.t_p {
-            println!();
-            Self::print_power_curve(decklist, false);
+            res.push(("t_p", "P/T curve", Self::power_curve(decklist, false)));
         }
 
         if self.hand {
-            println!();
-            Self::print_example_hand(decklist);
+            res.push(("hand", "Example Hand", Self::example_hand(decklist)));
         }
 
         if self.lands {
-            println!();
-            Self::print_lands(decklist);
+            res.push(("lands", "Land base", Self::lands(decklist)));
         }
 
         if self.pips {
-            println!();
-            Self::print_pips(decklist);
+            res.push(("pips", "Mana Symbols", Self::pips(decklist)));
         }
 
         if self.moxfield {
-            println!();
-            Self::print_moxfield(decklist);
+            res.push(("moxfield", "", Self::moxfield(decklist)));
         }
 
-        println!();
-
-        Ok(())
+        res
     }
 
-    pub fn print_cards<F>(list: &DeckList, listing: &str, filter: F)
+    pub fn cards<F>(list: &DeckList, filter: F) -> Report
     where
         F: Fn(&Proxy) -> bool,
     {
-        let cats = list.categories(&filter);
         let mut cards = list.card_names(&filter);
+        let mut categories = IndexMap::new();
 
-        println!("{} ({}):", listing, list.count_cards(&filter));
-
-        for (cat, names) in &cats {
-            let count: usize = names.iter().map(|s| cards.get(s).unwrap_or(&0usize)).sum();
-            println!("  {} ({}):", cat, count);
+        for (cat, names) in list.categories(&filter) {
+            let category: &mut IndexMap<_, _> = categories.entry(cat).or_default();
             for name in names {
-                let n = *cards.get(name).unwrap_or(&0usize);
-                if n == 1 {
-                    println!("    {}", name);
-                } else {
-                    println!("    {} x {}", n, name);
-                }
-                cards.remove(name);
+                let n = cards.remove(&name).unwrap_or(0);
+                category.insert(name, n);
             }
         }
+
+        Report::Cards(CardListing {
+            total: list.count_cards(&filter),
+            categories,
+        })
     }
 
-    pub fn print_creatures(decklist: &DeckList) {
+    pub fn creatures(decklist: &DeckList) -> Report {
         let names = decklist.card_names(Proxy::in_deck);
         let mut creatures = BTreeMap::new();
         for proxy in decklist {
@@ -183,18 +260,18 @@ impl List {
             }
         }
 
-        println!("Creatures:");
-        for (critter, pt) in creatures {
-            println!(
-                "  {} x {} {}",
-                names.get(&critter).unwrap_or(&0),
-                critter,
-                pt
-            );
-        }
+        Report::Creatures(
+            creatures
+                .into_iter()
+                .map(|(critter, pt)| {
+                    let count = *names.get(&critter).unwrap_or(&0);
+                    (critter, Creature { count, pt })
+                })
+                .collect(),
+        )
     }
 
-    pub fn print_power_curve(decklist: &DeckList, power: bool) {
+    pub fn power_curve(decklist: &DeckList, power: bool) -> Report {
         let mut pt_count = BTreeMap::new();
         for proxy in decklist {
             for card in &proxy.cardoid {
@@ -210,61 +287,54 @@ impl List {
             }
         }
 
-        println!("P/T curve:");
-        Self::print_histo(if power {
+        Report::Histogram(if power {
             pt_count
                 .into_iter()
                 .map(|((p, t), n)| (format!("{p}/{t}"), n))
-                .collect_vec()
+                .collect()
         } else {
             pt_count
                 .into_iter()
                 .map(|((t, p), n)| (format!("{p}/{t}"), n))
-                .collect_vec()
-        });
+                .collect()
+        })
     }
 
-    pub fn print_color_id(decklist: &DeckList) {
-        println!("Color Identity: {}", decklist.color_id().to_string())
+    pub fn color_id(decklist: &DeckList) -> Report {
+        Report::Identity(decklist.color_id().to_string())
     }
 
-    pub fn print_color_hist(decklist: &DeckList) {
-        println!("Color Histogram:");
-        Self::print_histo(
+    pub fn color_hist(decklist: &DeckList) -> Report {
+        Report::Histogram(
             decklist
                 .color_hist()
                 .into_iter()
                 .map(|s| (s.0.to_string(), s.1))
-                .collect_vec(),
-        );
+                .collect(),
+        )
     }
 
-    pub fn print_mana_curve(decklist: &DeckList) {
-        println!("Mana Curve:");
+    pub fn mana_curve(decklist: &DeckList) -> Report {
         let curve = decklist.curve();
         let Some(max) = curve.keys().max() else {
-            println!("  no curve");
-            return;
+            return Report::Curve(IndexMap::new());
         };
-        Self::print_histo(
+        Report::Curve(
             (0..=*max)
-                .into_iter()
                 .map(|n| (n.s(), *curve.get(&n).unwrap_or(&0)))
-                .collect_vec(),
-        );
+                .collect(),
+        )
     }
 
-    pub fn print_tag_hist(decklist: &DeckList) {
-        println!("Tags:");
-        Self::print_histo(decklist.tag_hist().into_iter().collect_vec());
+    pub fn tag_hist(decklist: &DeckList) -> Report {
+        Report::Histogram(decklist.tag_hist().into_iter().collect())
     }
 
-    pub fn print_type_hist(decklist: &DeckList) {
-        println!("Card Types:");
-        Self::print_histo(decklist.type_hist().into_iter().collect_vec());
+    pub fn type_hist(decklist: &DeckList) -> Report {
+        Report::Histogram(decklist.type_hist().into_iter().collect())
     }
 
-    pub fn print_example_hand(decklist: &DeckList) {
+    pub fn example_hand(decklist: &DeckList) -> Report {
         let mut names = decklist
             .iter()
             .flat_map(|x| vec![x.name.clone(); x.repeats])
@@ -273,65 +343,44 @@ impl List {
         let mut rng = rand::rngs::SmallRng::from_os_rng();
         names.shuffle(&mut rng);
 
-        let (hand, _) = names.split_at(7);
-        let mut hand = hand.to_owned();
-        hand.sort();
-        println!("Example Hand:");
-        for (i, n) in hand.iter().enumerate() {
-            println!("  {}. {n}", i + 1);
-        }
+        names.truncate(7);
+        names.sort();
+        Report::Hand(names)
     }
 
-    pub fn print_lands(decklist: &DeckList) {
-        println!("Land base:");
-        let mut basic = 0usize;
-        let mut basic_names = IndexSet::new();
-        let mut tapland = 0usize;
-        let mut tapland_names = IndexSet::new();
-        let mut nonmana = 0usize;
-        let mut nonmana_names = IndexSet::new();
-        let mut total = 0usize;
+    pub fn lands(decklist: &DeckList) -> Report {
+        let mut res = LandBase::default();
         for proxy in decklist {
             for land in &proxy.cardoid {
                 if !land.types.contains(&Type::Land) {
                     continue;
                 }
 
-                total += proxy.repeats;
+                res.total += proxy.repeats;
+
+                let add = |group: &mut LandGroup| {
+                    group.count += proxy.repeats;
+                    group.names.insert(land.name.clone());
+                };
 
                 if land.text.contains("enters tapped") {
-                    tapland += proxy.repeats;
-                    tapland_names.insert(land.name.clone());
+                    add(&mut res.tapland);
                 }
 
                 if land.supertypes.contains(&Supertype::Basic) {
-                    basic += proxy.repeats;
-                    basic_names.insert(land.name.clone());
+                    add(&mut res.basic);
                 }
 
                 if !land.text.contains("{T}: Add") {
-                    nonmana += proxy.repeats;
-                    nonmana_names.insert(land.name.clone());
+                    add(&mut res.nonmana);
                 }
             }
         }
 
-        println!("  {} x total", total);
-        println!("  {} x basic", basic);
-        for name in &basic_names {
-            println!("    {name}");
-        }
-        println!("  {} x tapland", tapland);
-        for name in &tapland_names {
-            println!("    {name}");
-        }
-        println!("  {} x nonmana land", nonmana);
-        for name in &nonmana_names {
-            println!("    {name}");
-        }
+        Report::Lands(res)
     }
 
-    pub fn print_creature_types(decklist: &DeckList) {
+    pub fn creature_types(decklist: &DeckList) -> Report {
         let mut types = BTreeMap::new();
         for proxy in decklist {
             for card in &proxy.cardoid {
@@ -344,62 +393,241 @@ impl List {
             }
         }
 
-        println!("Creature types:");
-        Self::print_histo(types.into_iter().collect_vec())
+        Report::Histogram(types.into_iter().collect())
     }
 
-    pub fn print_pips(decklist: &DeckList) {
+    pub fn pips(decklist: &DeckList) -> Report {
         let mut res = BTreeMap::new();
-        let re = Regex::new(r"\{.*?\}").unwrap();
         for proxy in decklist {
             if !proxy.in_deck() {
                 continue;
             }
             for card in &proxy.cardoid {
-                for pip in re.find_iter(&card.mana_cost) {
-                    *res.entry(pip.as_str().s()).or_insert(0) += proxy.repeats;
+                for (pie, n) in card.mana_cost.devotion() {
+                    *res.entry(format!("{{{}}}", pie)).or_insert(0) += n * proxy.repeats;
+                }
+                for symbol in card.mana_cost.iter().filter(|s| s.colors().is_empty()) {
+                    *res.entry(symbol.s()).or_insert(0) += proxy.repeats;
                 }
             }
         }
 
         let mut res = res.into_iter().collect_vec();
         res.sort_by_key(|x| -(x.1 as isize));
-        println!("Mana Symbols:");
-        Self::print_histo(res);
+        Report::Histogram(res.into_iter().collect())
+    }
+
+    fn moxfield(decklist: &DeckList) -> Report {
+        Report::Moxfield(
+            decklist
+                .iter()
+                .filter(|p| p.in_deck())
+                .map(|proxy| MoxfieldLine {
+                    count: proxy.repeats,
+                    name: proxy.name.clone(),
+                    tags: proxy.tags.clone(),
+                })
+                .collect(),
+        )
     }
+}
 
-    fn print_histo(things: Vec<(String, usize)>) {
-        let width = things.iter().map(|s| s.0.len()).max().unwrap_or(0) + 1;
+impl Report {
+    pub fn print(&self, title: &str) {
+        print!("{}", self.text(title));
+    }
 
-        for (mut thing, n) in things {
-            thing += &vec![" "; width - thing.len()].join("");
-            println!(
-                "  {thing}{}{}",
-                vec!["*"; n].join(""),
-                if n > 7 { format!(" ({n})") } else { "".s() }
-            );
+    pub fn text(&self, title: &str) -> String {
+        let mut out = String::new();
+        self.write(&mut out, title)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    fn write(&self, out: &mut impl Write, title: &str) -> std::fmt::Result {
+        match self {
+            Report::Identity(id) => writeln!(out, "{}: {}", title, id)?,
+            Report::Cards(listing) => {
+                writeln!(out, "{} ({}):", title, listing.total)?;
+                for (cat, names) in &listing.categories {
+                    writeln!(out, "  {} ({}):", cat, names.values().sum::<usize>())?;
+                    for (name, n) in names {
+                        if *n == 1 {
+                            writeln!(out, "    {}", name)?;
+                        } else {
+                            writeln!(out, "    {} x {}", n, name)?;
+                        }
+                    }
+                }
+            }
+            Report::Histogram(histo) => {
+                writeln!(out, "{}:", title)?;
+                Self::write_histo(out, histo)?;
+            }
+            Report::Curve(curve) => {
+                writeln!(out, "{}:", title)?;
+                if curve.is_empty() {
+                    writeln!(out, "  no curve")?;
+                }
+                Self::write_histo(out, curve)?;
+            }
+            Report::Creatures(creatures) => {
+                writeln!(out, "{}:", title)?;
+                for (critter, creature) in creatures {
+                    writeln!(out, "  {} x {} {}", creature.count, critter, creature.pt)?;
+                }
+            }
+            Report::Hand(hand) => {
+                writeln!(out, "{}:", title)?;
+                for (i, n) in hand.iter().enumerate() {
+                    writeln!(out, "  {}. {n}", i + 1)?;
+                }
+            }
+            Report::Lands(lands) => {
+                writeln!(out, "{}:", title)?;
+                writeln!(out, "  {} x total", lands.total)?;
+                for (_, label, group) in lands.groups() {
+                    writeln!(out, "  {} x {}", group.count, label)?;
+                    for name in &group.names {
+                        writeln!(out, "    {name}")?;
+                    }
+                }
+            }
+            Report::Moxfield(lines) => {
+                for line in lines {
+                    if line.tags.is_empty() {
+                        writeln!(out, "{} {}", line.count, line.name)?;
+                    } else {
+                        writeln!(
+                            out,
+                            "{} {} {}",
+                            line.count,
+                            line.name,
+                            line.tags.iter().map(|s| format!("#{}", s)).join("")
+                        )?;
+                    }
+                }
+            }
         }
+
+        Ok(())
     }
 
-    fn print_moxfield(decklist: &DeckList) {
-        for proxy in decklist {
-            if proxy.in_deck() {
-                if proxy.tags.is_empty() {
-                    println!("{} {}", proxy.repeats, proxy.name,);
-                } else {
-                    println!(
-                        "{} {} {}",
-                        proxy.repeats,
-                        proxy.name,
-                        proxy
-                            .tags
-                            .iter()
-                            .map(|s| format!("#{}", s))
-                            .collect_vec()
-                            .join("")
-                    );
+    pub fn rows<'a>(&self, report: &'a str) -> Vec<ReportRow<'a>> {
+        let row = |group: &str, name: &str, count| ReportRow {
+            report,
+            group: group.s(),
+            name: name.s(),
+            count,
+        };
+
+        match self {
+            Report::Identity(id) => vec![row("", id, None)],
+            Report::Cards(listing) => listing
+                .categories
+                .iter()
+                .flat_map(|(cat, names)| names.iter().map(move |(name, n)| (cat, name, n)))
+                .map(|(cat, name, n)| row(cat, name, Some(*n)))
+                .collect(),
+            Report::Histogram(histo) | Report::Curve(histo) => histo
+                .iter()
+                .map(|(name, n)| row("", name, Some(*n)))
+                .collect(),
+            Report::Creatures(creatures) => creatures
+                .iter()
+                .map(|(name, c)| row(&c.pt, name, Some(c.count)))
+                .collect(),
+            Report::Hand(hand) => hand.iter().map(|name| row("", name, None)).collect(),
+            Report::Lands(lands) => {
+                let mut res = vec![row("total", "", Some(lands.total))];
+                for (group, _, lands) in lands.groups() {
+                    res.push(row(group, "", Some(lands.count)));
+                    res.extend(lands.names.iter().map(|name| row(group, name, None)));
                 }
+                res
             }
+            Report::Moxfield(lines) => lines
+                .iter()
+                .map(|line| row(&line.tags.iter().join(" "), &line.name, Some(line.count)))
+                .collect(),
         }
     }
+
+    fn write_histo(out: &mut impl Write, things: &IndexMap<String, usize>) -> std::fmt::Result {
+        let width = things.keys().map(|s| s.len()).max().unwrap_or(0) + 1;
+
+        for (thing, n) in things {
+            let thing = thing.clone() + &vec![" "; width - thing.len()].join("");
+            writeln!(
+                out,
+                "  {thing}{}{}",
+                vec!["*"; *n].join(""),
+                if *n > 7 { format!(" ({n})") } else { "".s() }
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl LandBase {
+    fn groups(&self) -> [(&'static str, &'static str, &LandGroup); 3] {
+        [
+            ("basic", "basic", &self.basic),
+            ("tapland", "tapland", &self.tapland),
+            ("nonmana", "nonmana land", &self.nonmana),
+        ]
+    }
+}
+
+#[test]
+fn test_list_reports() {
+    use crate::atomic_cards::{cardoids::Cardoid, cards::Card};
+
+    let proxy = |name: &str, mana_value: f64, tag: &str, repeats: usize| Proxy {
+        name: name.s(),
+        repeats,
+        tags: IndexSet::from_iter([tag.s()]),
+        cardoid: Cardoid::from(vec![Card {
+            name: name.s(),
+            types: vec![Type::Creature],
+            mana_value,
+            power: "1".s(),
+            toughness: "1".s(),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+
+    let mut decklist = DeckList::new();
+    decklist.add_card(proxy("Llanowar Elves", 1.0, "Ramp", 2));
+    decklist.add_card(proxy("Wall of Omens", 2.0, "Draw", 1));
+
+    let list = List {
+        curve: true,
+        cards: true,
+        ..List::try_parse_from(["list", "deck.json"]).unwrap()
+    };
+    let reports = list.reports(&decklist);
+
+    assert_eq!(
+        serde_json::to_value(&reports[1].2).unwrap(),
+        serde_json::json!({"0": 0, "1": 2, "2": 1})
+    );
+    assert_eq!(
+        serde_json::to_value(&reports[0].2).unwrap(),
+        serde_json::json!({
+            "total": 3,
+            "categories": {"Ramp": {"Llanowar Elves": 2}, "Draw": {"Wall of Omens": 1}},
+        })
+    );
+    assert_eq!(
+        reports[0].2.rows("cards")[0],
+        ReportRow {
+            report: "cards",
+            group: "Ramp".s(),
+            name: "Llanowar Elves".s(),
+            count: Some(2),
+        }
+    );
 }
@@ -1,13 +1,23 @@
 pub mod build;
+pub mod db;
+pub mod diff;
 pub mod list;
+pub mod output;
 pub mod search;
+pub mod serve;
 pub mod setup;
+pub mod shell;
+pub mod sql;
+pub mod update;
 
-use std::{fmt::Debug, path::Path};
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
 
 use clap::{Parser, Subcommand};
 
-use crate::{atomic_cards::AtomicCardsFile, proxy::decklists::DeckList};
+use crate::{atomic_cards::source::CardSource, proxy::decklists::DeckList};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -16,21 +26,29 @@ pub struct Command {
     pub subcommand: ListBuildSearch,
     #[arg(short, long)]
     pub verbose: bool,
+    #[arg(long, global = true, env = "PROXYGENITUS_DATA_DIR", value_name = "DIR")]
+    pub data_dir: Option<PathBuf>,
+    #[arg(long, global = true, value_name = "FILE")]
+    pub cards_file: Vec<PathBuf>,
 }
 
-#[allow(unused)]
-pub struct Context {
-    pub atomics: AtomicCardsFile,
-    pub decklist: DeckList,
-}
+impl Command {
+    const DATA_DIR_NAME: &'static str = "proxygenitus";
 
-#[allow(unused)]
-impl Context {
-    pub fn load_atomics(&mut self) -> anyhow::Result<()> {
-        todo!()
-    }
-    pub fn load_decklist(&mut self, _path: &Path) -> anyhow::Result<()> {
-        todo!()
+    pub fn data_dir(&self) -> anyhow::Result<PathBuf> {
+        let dir = if let Some(dir) = &self.data_dir {
+            dir.clone()
+        } else if let Some(cache) = std::env::var_os("XDG_CACHE_HOME").filter(|s| !s.is_empty()) {
+            PathBuf::from(cache).join(Self::DATA_DIR_NAME)
+        } else if let Some(home) = std::env::var_os("HOME").filter(|s| !s.is_empty()) {
+            PathBuf::from(home).join(".cache").join(Self::DATA_DIR_NAME)
+        } else {
+            PathBuf::from(".")
+        };
+
+        std::fs::create_dir_all(&dir)?;
+
+        Ok(dir)
     }
 }
 
@@ -39,7 +57,14 @@ pub enum ListBuildSearch {
     List(list::List),
     Build(build::Build),
     Search(search::Search),
-    // Setup(setup::Setup),
+    Setup(setup::Setup),
+    Update(update::Update),
+    Diff(diff::Diff),
+    #[command(alias = "repl")]
+    Shell(shell::Shell),
+    Serve(serve::Serve),
+    Sql(sql::Sql),
+    Db(db::Db),
 }
 
 impl ListBuildSearch {
@@ -48,18 +73,45 @@ impl ListBuildSearch {
             ListBuildSearch::List(list) => list.decklist_file(),
             ListBuildSearch::Build(build) => build.decklist_file(),
             ListBuildSearch::Search(search) => search.decklist_file(),
+            ListBuildSearch::Setup(setup) => setup.decklist_file(),
+            ListBuildSearch::Update(update) => update.decklist_file(),
+            ListBuildSearch::Diff(diff) => diff.decklist_file(),
+            ListBuildSearch::Shell(shell) => shell.decklist_file(),
+            ListBuildSearch::Serve(serve) => serve.decklist_file(),
+            ListBuildSearch::Sql(sql) => sql.decklist_file(),
+            ListBuildSearch::Db(db) => db.decklist_file(),
         }
     }
 
+    pub fn uses_card_data(&self) -> bool {
+        !matches!(
+            self,
+            ListBuildSearch::Setup(_)
+                | ListBuildSearch::Update(_)
+                | ListBuildSearch::Diff(_)
+                | ListBuildSearch::Sql(_)
+                | ListBuildSearch::Db(_)
+        )
+    }
+
     pub fn dispatch(
         self,
-        atomics: &AtomicCardsFile,
+        verbose: bool,
+        data_dir: &Path,
+        atomics: &dyn CardSource,
         decklist: &mut DeckList,
     ) -> anyhow::Result<()> {
         match self {
             Self::List(l) => l.dispatch(decklist),
-            Self::Build(b) => b.dispatch(decklist),
+            Self::Build(b) => b.dispatch(data_dir, decklist),
             Self::Search(s) => s.dispatch(atomics, decklist),
+            Self::Setup(s) => s.dispatch(data_dir, verbose),
+            Self::Update(u) => u.dispatch(data_dir, verbose),
+            Self::Diff(d) => d.dispatch(),
+            Self::Shell(s) => s.dispatch(data_dir, atomics, decklist),
+            Self::Serve(s) => s.dispatch(data_dir, atomics, decklist),
+            Self::Sql(s) => s.dispatch(data_dir, verbose),
+            Self::Db(d) => d.dispatch(data_dir, verbose),
         }
     }
 }
@@ -0,0 +1,73 @@
+use std::io::Write;
+
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::Serialize;
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+    Ndjson,
+}
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<()> {
+    let mut out = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut out, value)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+// CSV records have to be flat, nested values are only allowed in JSON.
+pub fn print_records<T, I>(format: Format, records: I) -> anyhow::Result<()>
+where
+    T: Serialize,
+    I: IntoIterator<Item = T>,
+{
+    let mut out = std::io::stdout().lock();
+
+    match format {
+        Format::Text => unreachable!("text output is printed by each command"),
+        Format::Json => print_json(&records.into_iter().collect_vec())?,
+        Format::Ndjson => {
+            for record in records {
+                serde_json::to_writer(&mut out, &record)?;
+                writeln!(out)?;
+            }
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for record in records {
+                writer.serialize(record)?;
+            }
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+// Left-aligned columns, as wide as their widest cell.
+pub fn print_table(rows: &[Vec<String>]) {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect_vec();
+
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
@@ -1,13 +1,31 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use clap::Parser;
-use indexmap::IndexSet;
+use indexmap::IndexMap;
 use itertools::Itertools;
 use regex::Regex;
+use serde::Serialize;
 
 use crate::{
-    atomic_cards::{cardoids::Cardoid, cards::Card, types::WUBRG, AtomicCardsFile},
-    proxy::{decklists::DeckList, Proxy},
+    atomic_cards::{
+        cardoids::Cardoid, cards::Card, mana::ManaCost, metadata::Legality, source::CardSource,
+        types::WUBRG,
+    },
+    command::output::{self, Format},
+    proxy::{
+        decklists::{tag_label, DeckList},
+        Proxy,
+    },
+    query::{
+        comparison::{CompareOp, Comparison},
+        parser,
+        sort::SortKey,
+        Filter, Query, Subject,
+    },
     utils::ToS,
 };
 
@@ -27,12 +45,37 @@ pub struct Search {
     pub r#type: Vec<String>,
     #[arg(long)]
     pub vtype: Vec<String>,
-    #[arg(long)]
+    #[arg(
+        long,
+        value_name = "REGEX",
+        help = "Rules text matching the regex, checked card by card; see --fulltext for the index"
+    )]
     pub text: Vec<String>,
     #[arg(long)]
     pub vtext: Vec<String>,
     #[arg(long)]
+    pub mana: Option<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    pub mv: Vec<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    pub power: Vec<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    pub toughness: Vec<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    pub loyalty: Vec<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    pub defense: Vec<String>,
+    #[arg(long)]
     pub grep: Vec<String>,
+    // Words, not regexes: the index stems them, so "elf" doesn't find "self"
+    // the way --text elf does. That's why --text keeps its own scan.
+    #[arg(
+        long,
+        alias = "fts",
+        value_name = "QUERY",
+        help = "Word search over names, types and text using the text index, best match first"
+    )]
+    pub fulltext: Option<String>,
     #[arg(long)]
     pub vgrep: Vec<String>,
     #[arg(long)]
@@ -41,6 +84,46 @@ pub struct Search {
     pub debug: bool,
     #[arg(long)]
     pub case_sensitive: bool,
+    #[arg(long)]
+    pub language: Option<String>,
+    #[arg(long)]
+    pub legal: Vec<String>,
+    #[arg(long)]
+    pub banned: Vec<String>,
+    #[arg(long)]
+    pub restricted: Vec<String>,
+    #[arg(long)]
+    pub can_be_commander: bool,
+    #[arg(long)]
+    pub can_be_brawl_commander: bool,
+    #[arg(long)]
+    pub can_be_oathbreaker: bool,
+    #[arg(
+        long,
+        help = "Suggest cards for the deck, legal in the --legal formats or in commander when the deck has one"
+    )]
+    pub upgrades: bool,
+    #[arg(long, value_name = "PATTERN")]
+    pub rank: Vec<String>,
+    #[arg(long, short)]
+    pub query: Option<String>,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+    #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+    pub sort: Vec<String>,
+    #[arg(long)]
+    pub reverse: bool,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "COLUMNS",
+        help = "Print a table instead, e.g. name,cost,type,pt (also mv, colors, identity, count, tags)"
+    )]
+    pub columns: Vec<String>,
+    #[arg(long)]
+    pub count: bool,
+    #[arg(long)]
+    pub limit: Option<usize>,
     #[arg(value_name = "OFILE")]
     pub decklist: Option<PathBuf>,
 }
@@ -53,58 +136,394 @@ impl Search {
             .unwrap_or(Path::new(""))
     }
 
-    pub fn dispatch(self, atomics: &AtomicCardsFile, decklist: &DeckList) -> anyhow::Result<()> {
+    pub fn dispatch(self, atomics: &dyn CardSource, decklist: &DeckList) -> anyhow::Result<()> {
+        self.run(atomics, decklist)?;
+        Ok(())
+    }
+
+    pub fn run(self, atomics: &dyn CardSource, decklist: &DeckList) -> anyhow::Result<usize> {
         let searcher = Searcher::new(self)?;
+        let hits = searcher.find(atomics, decklist)?;
+        let count = hits.copies();
 
-        if decklist.is_empty() {
-            let mut hits = searcher.matches_cardoids(atomics.data.values());
-            hits.sort_by_key(|c| c.name());
-            hits.iter().for_each(|c| searcher.print_cardoid(*c));
-        } else {
-            let mut hits = searcher.match_proxies(decklist);
-            hits.sort_by_key(|p| (p.category(), &p.name));
-            hits.iter().for_each(|p| searcher.print_proxy(*p));
+        match hits {
+            Hits::Cards(hits) => searcher.print_cardoids(hits)?,
+            Hits::Proxies(hits) => searcher.print_proxies(hits)?,
         }
 
-        Ok(())
+        Ok(count)
+    }
+}
+
+pub enum Hits {
+    Cards(Vec<Cardoid>),
+    Proxies(Vec<Proxy>),
+}
+
+impl Hits {
+    // What --count prints and the shell reports: copies, not distinct cards.
+    pub fn copies(&self) -> usize {
+        match self {
+            Hits::Cards(hits) => hits.len(),
+            Hits::Proxies(hits) => hits.iter().map(|p| p.repeats).sum(),
+        }
     }
 }
 
-struct Searcher {
-    tags: IndexSet<String>,
-    color: WUBRG,
-    commander: WUBRG,
-    name: Vec<Regex>,
-    vname: Vec<Regex>,
-    r#type: Vec<Regex>,
-    vtype: Vec<Regex>,
-    grep: Vec<Regex>,
-    vgrep: Vec<Regex>,
-    text: Vec<Regex>,
-    vtext: Vec<Regex>,
+pub struct Searcher {
+    query: Query,
+    fulltext: Option<String>,
     sideboard: bool,
     debug: bool,
+    language: Option<String>,
+    upgrades: bool,
+    any_legal: bool,
+    rank: Vec<Regex>,
+    format: Format,
+    sort: Vec<SortKey>,
+    reverse: bool,
+    columns: Vec<Column>,
+    count: bool,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    Cost,
+    ManaValue,
+    Type,
+    PowerToughness,
+    Colors,
+    Identity,
+    Count,
+    Tags,
+}
+
+impl FromStr for Column {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "name" => Column::Name,
+            "cost" | "mana" => Column::Cost,
+            "mv" | "cmc" => Column::ManaValue,
+            "type" => Column::Type,
+            "pt" | "p/t" => Column::PowerToughness,
+            "color" | "colors" => Column::Colors,
+            "id" | "identity" => Column::Identity,
+            "count" => Column::Count,
+            "tags" => Column::Tags,
+            _ => anyhow::bail!(
+                "Unknown column '{}', expected one of: name, cost, mv, type, pt, colors, identity, count, tags",
+                s
+            ),
+        })
+    }
+}
+
+impl Column {
+    pub fn cell(&self, row: &CardRow, cardoid: &Cardoid) -> String {
+        let face = cardoid.face();
+
+        match self {
+            Column::Name => row.name.clone(),
+            Column::Cost => row.mana_cost.clone(),
+            Column::ManaValue => row.mana_value.s(),
+            Column::Type => row.type_line.clone(),
+            Column::PowerToughness if face.power.is_empty() && face.toughness.is_empty() => {
+                format!("{}{}", face.loyalty, face.defense)
+            }
+            Column::PowerToughness => format!("{}/{}", face.power, face.toughness),
+            Column::Colors => row.colors.clone(),
+            Column::Identity => row.color_identity.clone(),
+            Column::Count => row.count.map(|n| n.s()).unwrap_or_default(),
+            Column::Tags => row.tags.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CardRow {
+    pub name: String,
+    pub mana_cost: String,
+    pub mana_value: f64,
+    pub type_line: String,
+    pub colors: String,
+    pub color_identity: String,
+    pub power: String,
+    pub toughness: String,
+    pub loyalty: String,
+    pub defense: String,
+    pub text: String,
+    pub count: Option<usize>,
+    pub tags: Option<String>,
+}
+
+impl CardRow {
+    pub fn new(cardoid: &Cardoid) -> Self {
+        let faces = |field: fn(&Card) -> String| {
+            let values = cardoid.iter().map(field).collect_vec();
+            if values.iter().all(String::is_empty) {
+                String::new()
+            } else {
+                values.join(" // ")
+            }
+        };
+
+        Self {
+            name: cardoid.name().s(),
+            mana_cost: faces(|c| c.mana_cost.s()),
+            mana_value: cardoid.face().mana_value,
+            type_line: faces(|c| c.type_line.clone()),
+            colors: faces(|c| c.colors.to_string()),
+            color_identity: cardoid.color_identity().to_string(),
+            power: faces(|c| c.power.clone()),
+            toughness: faces(|c| c.toughness.clone()),
+            loyalty: faces(|c| c.loyalty.clone()),
+            defense: faces(|c| c.defense.clone()),
+            text: faces(|c| c.text.clone()),
+            count: None,
+            tags: None,
+        }
+    }
+}
+
+impl From<&Proxy> for CardRow {
+    fn from(proxy: &Proxy) -> Self {
+        Self {
+            count: Some(proxy.repeats),
+            tags: Some(proxy.tags.iter().join(" ")),
+            ..Self::new(&proxy.cardoid)
+        }
+    }
 }
 
 impl Searcher {
-    fn new(it: Search) -> anyhow::Result<Self> {
+    pub fn new(it: Search) -> anyhow::Result<Self> {
+        let case = it.case_sensitive;
+        let mut queries = vec![];
+        let mut face = vec![];
+
+        queries.extend(it.tag.into_iter().map(|t| Filter::Tag(t).into()));
+        if let Some(color) = it.color {
+            face.push(Filter::Color(CompareOp::Ge, Self::build_color(color)).into());
+        }
+        if let Some(commander) = it.commander {
+            queries.push(Filter::Identity(CompareOp::Le, Self::build_color(commander)).into());
+        }
+        if let Some(mana) = it.mana {
+            queries.push(Filter::Mana(ManaCost::parse_shorthand(&mana)).into());
+        }
+
+        // --color, --type and --text have to hold on one face together, a
+        // creature front with a flying back doesn't match --type creature
+        // --text flying.
+        for (pos, neg, filter, on_face) in [
+            (
+                it.name,
+                it.vname,
+                Filter::Name as fn(Regex) -> Filter,
+                false,
+            ),
+            (it.r#type, it.vtype, Filter::Type, true),
+            (it.text, it.vtext, Filter::Oracle, true),
+            (it.grep, it.vgrep, Filter::Grep, false),
+        ] {
+            let target = if on_face { &mut face } else { &mut queries };
+            for r in Self::build_regexes(case, pos)? {
+                target.push(filter(r).into());
+            }
+            for r in Self::build_regexes(case, neg)? {
+                target.push(Query::not(filter(r).into()));
+            }
+        }
+        if !face.is_empty() {
+            queries.push(Query::Face(Box::new(Query::all(face))));
+        }
+
+        for (comparisons, filter) in [
+            (it.mv, Filter::ManaValue as fn(Comparison) -> Filter),
+            (it.power, Filter::Power),
+            (it.toughness, Filter::Toughness),
+            (it.loyalty, Filter::Loyalty),
+            (it.defense, Filter::Defense),
+        ] {
+            for c in comparisons {
+                queries.push(filter(c.parse()?).into());
+            }
+        }
+
+        // find_upgrades picks the format from the deck unless one is given.
+        let any_legal = !it.legal.is_empty();
+
+        for (formats, legality) in [
+            (it.legal, Legality::Legal),
+            (it.banned, Legality::Banned),
+            (it.restricted, Legality::Restricted),
+        ] {
+            for format in formats {
+                queries.push(Filter::legality(&format, legality)?.into());
+            }
+        }
+
+        for (format, enabled) in [
+            ("commander", it.can_be_commander),
+            ("brawl", it.can_be_brawl_commander),
+            ("oathbreaker", it.can_be_oathbreaker),
+        ] {
+            if enabled {
+                queries.push(Filter::can_lead(format)?.into());
+            }
+        }
+
+        if let Some(query) = it.query {
+            queries.push(parser::parse(&query, case)?);
+        }
+
         Ok(Self {
-            tags: IndexSet::from_iter(it.tag.into_iter()),
-            name: Self::build_regexes(it.case_sensitive, it.name)?,
-            vname: Self::build_regexes(it.case_sensitive, it.vname)?,
-            color: Self::build_color(it.color, WUBRG::colorless())?,
-            commander: Self::build_color(it.commander, WUBRG::wubrg())?,
-            r#type: Self::build_regexes(it.case_sensitive, it.r#type)?,
-            vtype: Self::build_regexes(it.case_sensitive, it.vtype)?,
-            grep: Self::build_regexes(it.case_sensitive, it.grep)?,
-            vgrep: Self::build_regexes(it.case_sensitive, it.vgrep)?,
-            text: Self::build_regexes(it.case_sensitive, it.text)?,
-            vtext: Self::build_regexes(it.case_sensitive, it.vtext)?,
+            query: Query::all(queries),
+            fulltext: it.fulltext,
             debug: it.debug,
             sideboard: it.sideboard,
+            language: it.language,
+            upgrades: it.upgrades,
+            any_legal,
+            rank: Self::build_regexes(case, it.rank)?,
+            format: it.format,
+            sort: it
+                .sort
+                .iter()
+                .map(|s| s.parse())
+                .collect::<anyhow::Result<_>>()?,
+            reverse: it.reverse,
+            columns: it
+                .columns
+                .iter()
+                .map(|s| s.parse())
+                .collect::<anyhow::Result<_>>()?,
+            count: it.count,
+            limit: it.limit,
         })
     }
 
+    // Searches the deck's proxies when there is a deck, the whole database
+    // otherwise. Results are sorted but not limited.
+    pub fn find(&self, atomics: &dyn CardSource, decklist: &DeckList) -> anyhow::Result<Hits> {
+        let ranked = match &self.fulltext {
+            Some(query) => Some(IndexMap::<_, _>::from_iter(atomics.text_search(query)?)),
+            None => None,
+        };
+        let found = |name: &str| ranked.as_ref().is_none_or(|r| r.contains_key(name));
+
+        if self.upgrades {
+            if decklist.is_empty() {
+                anyhow::bail!("--upgrades needs a decklist to find cards for");
+            }
+            let mut hits = self.find_upgrades(atomics, decklist)?;
+            hits.retain(|c| found(c.name()));
+            self.arrange(&mut hits, |c| c);
+            Ok(Hits::Cards(hits))
+        } else if !decklist.is_empty() {
+            let rank = |name: &str| ranked.as_ref().and_then(|r| r.get_index_of(name));
+            let mut hits = self.match_proxies(decklist);
+            hits.retain(|p| found(&p.name));
+            hits.sort_by_key(|p| (rank(&p.name), p.category(), &p.name));
+            self.arrange(&mut hits, |p| &p.cardoid);
+            Ok(Hits::Proxies(hits.into_iter().cloned().collect()))
+        } else if let Some(ranked) = &ranked {
+            // Only the cards the text index found get loaded, best match first.
+            let mut hits = vec![];
+            for name in ranked.keys() {
+                if let Some(c) = atomics.lookup(name)?.filter(|c| self.matches_cardoid(c)) {
+                    hits.push(c);
+                }
+            }
+            self.arrange(&mut hits, |c| c);
+            Ok(Hits::Cards(hits))
+        } else {
+            let mut hits = vec![];
+            atomics.for_each_matching(&self.query, &mut |_, c| {
+                if self.matches_cardoid(c) {
+                    hits.push(c.clone());
+                }
+                Ok(())
+            })?;
+            hits.sort_by(|a, b| a.name().cmp(b.name()));
+            self.arrange(&mut hits, |c| c);
+            Ok(Hits::Cards(hits))
+        }
+    }
+
+    // Ranks by how many of the deck's tags and the --rank patterns a card
+    // mentions, e.g. a deck with a "Ramp" or "Treasure" category.
+    fn find_upgrades(
+        &self,
+        atomics: &dyn CardSource,
+        decklist: &DeckList,
+    ) -> anyhow::Result<Vec<Cardoid>> {
+        let owned: HashSet<&str> = decklist.iter().map(|p| p.name.as_str()).collect();
+        let mut fits = Query::from(Filter::Identity(
+            CompareOp::Le,
+            decklist.commander_identity(),
+        ));
+        if !self.any_legal {
+            if decklist.commanders().is_empty() {
+                anyhow::bail!("The deck has no commander, pick a format with --legal");
+            }
+            fits = Query::And(vec![
+                fits,
+                Filter::legality("commander", Legality::Legal)?.into(),
+            ]);
+        }
+
+        let mut patterns = self.rank.clone();
+        for tag in decklist.tag_hist().keys() {
+            if let Some(pattern) = Self::tag_pattern(tag) {
+                patterns.push(Regex::new(&pattern)?);
+            }
+        }
+
+        let mut hits = vec![];
+        let query = Query::And(vec![fits.clone(), self.query.clone()]);
+        atomics.for_each_matching(&query, &mut |_, c| {
+            if !owned.contains(c.name())
+                && fits.matches(&Subject::new(c))
+                && self.matches_cardoid(c)
+            {
+                let text = c.to_string();
+                let score = patterns.iter().filter(|r| r.is_match(&text)).count();
+                hits.push((score, c.clone()));
+            }
+            Ok(())
+        })?;
+
+        hits.sort_by(|(a_score, a), (b_score, b)| {
+            b_score.cmp(a_score).then_with(|| a.name().cmp(b.name()))
+        });
+        Ok(hits.into_iter().map(|(_, c)| c).collect())
+    }
+
+    // What a card that belongs under the tag tends to say. Category names
+    // rarely show up in rules text as is, nothing says "card advantage".
+    fn tag_pattern(tag: &str) -> Option<String> {
+        let label = tag_label(tag);
+        let pattern = match label.to_lowercase().as_str() {
+            "commander" | "lands" | "sideboard" => return None,
+            "card advantage" | "card draw" | "draw" => r"\bdraws? .*\bcards?\b",
+            "tutors" | "tutor" => r"\bsearch (your|their) library\b",
+            "ramp" | "land advantage" => {
+                r"\badd \{|\bsearch your library for .*\blands?\b|\badditional lands?\b"
+            }
+            "removal" | "control" => r"\b(destroy|exile|counter) target\b",
+            "board protection" | "commander protection" | "protection" => {
+                r"\b(hexproof|indestructible|shroud|protection from)\b"
+            }
+            _ => return Some(format!(r"(?i)\b{}\b", regex::escape(label))),
+        };
+        Some(format!("(?i){}", pattern))
+    }
+
     fn match_proxies<'a>(&self, proxies: impl IntoIterator<Item = &'a Proxy>) -> Vec<&'a Proxy> {
         proxies
             .into_iter()
@@ -113,41 +532,26 @@ impl Searcher {
             .collect_vec()
     }
 
-    fn matches_cardoids<'a>(
-        &self,
-        cardoids: impl IntoIterator<Item = &'a Cardoid>,
-    ) -> Vec<&'a Cardoid> {
-        cardoids
-            .into_iter()
-            .filter(|c| self.matches_cardoid(c))
-            .collect_vec()
-    }
-
     fn matches_proxy(&self, proxy: &Proxy) -> bool {
-        self.tags.is_subset(&proxy.tags) && self.matches_cardoid(&proxy.cardoid)
+        self.query.matches(&Subject {
+            tags: Some(&proxy.tags),
+            language: self.language.as_deref(),
+            ..Subject::new(&proxy.cardoid)
+        })
     }
 
     fn matches_cardoid(&self, cardoid: &Cardoid) -> bool {
-        cardoid.color_identity().0.is_subset(&self.commander.0)
-            && Self::regex_match(&self.name, &self.vname, cardoid.name())
-            && Self::regex_match(&self.grep, &self.vgrep, &format!("{}", cardoid))
-            && cardoid.iter().any(|card| self.matches_card(card))
-    }
-
-    fn matches_card(&self, card: &Card) -> bool {
-        self.color.0.is_subset(&card.colors.0)
-            && Self::regex_match(&self.r#type, &self.vtype, &card.type_line)
-            && Self::regex_match(&self.text, &self.vtext, &card.text)
+        self.query.matches(&Subject {
+            language: self.language.as_deref(),
+            ..Subject::new(cardoid)
+        })
     }
 
-    fn build_color(it: Option<String>, or: WUBRG) -> anyhow::Result<WUBRG> {
-        let Some(it) = it else {
-            return Ok(or);
-        };
+    fn build_color(it: String) -> WUBRG {
         if it == "C" || it == "c" {
-            return Ok(WUBRG::colorless());
+            return WUBRG::colorless();
         }
-        return Ok(WUBRG::from(it));
+        WUBRG::from(it)
     }
 
     fn build_regexes(case: bool, it: Vec<String>) -> anyhow::Result<Vec<Regex>> {
@@ -159,14 +563,85 @@ impl Searcher {
         Ok(res)
     }
 
-    fn regex_match(pos: &[Regex], neg: &[Regex], data: &str) -> bool {
-        pos.iter().all(|r| r.is_match(data)) && !neg.iter().any(|r| r.is_match(data))
+    // The sort is stable, so cards that tie on every --sort key keep the
+    // default name (or upgrade score) order.
+    fn arrange<T>(&self, hits: &mut [T], cardoid: impl Fn(&T) -> &Cardoid) {
+        hits.sort_by(|a, b| SortKey::compare_all(&self.sort, cardoid(a), cardoid(b)));
+        if self.reverse {
+            hits.reverse();
+        }
+    }
+
+    fn print_cardoids(&self, mut hits: Vec<Cardoid>) -> anyhow::Result<()> {
+        if self.count {
+            println!("{}", hits.len());
+            return Ok(());
+        }
+
+        hits.truncate(self.limit.unwrap_or(usize::MAX));
+
+        if self.format == Format::Text && self.columns.is_empty() {
+            hits.iter().for_each(|c| self.print_cardoid(c));
+            return Ok(());
+        }
+
+        if let Some(language) = &self.language {
+            hits = hits.iter().map(|c| c.localized(language)).collect();
+        }
+        match self.format {
+            Format::Text => self.print_table(hits.iter().map(|c| (CardRow::new(c), c))),
+            // Same fields in every machine format, whatever --columns says.
+            format => output::print_records(format, hits.iter().map(CardRow::new)),
+        }
+    }
+
+    fn print_proxies(&self, mut hits: Vec<Proxy>) -> anyhow::Result<()> {
+        if self.count {
+            println!("{}", Hits::Proxies(hits).copies());
+            return Ok(());
+        }
+
+        hits.truncate(self.limit.unwrap_or(usize::MAX));
+
+        if self.format == Format::Text && self.columns.is_empty() {
+            hits.iter().for_each(|p| self.print_proxy(p));
+            return Ok(());
+        }
+
+        if let Some(language) = &self.language {
+            for proxy in &mut hits {
+                proxy.cardoid = proxy.cardoid.localized(language);
+            }
+        }
+        match self.format {
+            Format::Text => self.print_table(hits.iter().map(|p| (CardRow::from(p), &p.cardoid))),
+            format => output::print_records(format, hits.iter().map(CardRow::from)),
+        }
+    }
+
+    fn print_table<'a>(
+        &self,
+        rows: impl Iterator<Item = (CardRow, &'a Cardoid)>,
+    ) -> anyhow::Result<()> {
+        let cells = rows
+            .map(|(row, cardoid)| {
+                self.columns
+                    .iter()
+                    .map(|c| c.cell(&row, cardoid).replace('\n', " "))
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        output::print_table(&cells);
+        Ok(())
     }
 
     fn print_cardoid(&self, c: &Cardoid) {
         println!();
         if self.debug {
             println!("{:?}", c);
+        } else if let Some(language) = &self.language {
+            println!("{}", c.localized(language));
         } else {
             println!("{}", c);
         }
@@ -176,8 +651,114 @@ impl Searcher {
         println!();
         if self.debug {
             println!("{:?}", p);
+        } else if let Some(language) = &self.language {
+            let mut p = p.clone();
+            p.cardoid = p.cardoid.localized(language);
+            println!("{}", p);
         } else {
             println!("{}", p);
         }
     }
 }
+
+#[test]
+fn test_search() -> anyhow::Result<()> {
+    use crate::atomic_cards::{metadata::Legalities, types::Side};
+    use indexmap::IndexSet;
+
+    let search = |args: &[&str]| {
+        Searcher::new(Search::try_parse_from(["search"].iter().chain(args)).unwrap()).unwrap()
+    };
+
+    // A creature front with a flying back.
+    let mdfc = Cardoid::from(vec![
+        Card {
+            name: "Front // Back".s(),
+            type_line: "Creature — Elf".s(),
+            side: Side::A,
+            ..Default::default()
+        },
+        Card {
+            name: "Front // Back".s(),
+            type_line: "Instant".s(),
+            text: "Flying".s(),
+            side: Side::B,
+            ..Default::default()
+        },
+    ]);
+    assert!(search(&["--type", "creature"]).matches_cardoid(&mdfc));
+    assert!(search(&["--type", "creature", "--vtext", "flying"]).matches_cardoid(&mdfc));
+    assert!(!search(&["--type", "creature", "--text", "flying"]).matches_cardoid(&mdfc));
+    assert!(search(&["--type", "instant", "--text", "flying"]).matches_cardoid(&mdfc));
+    assert!(search(&["-q", "t:creature o:flying"]).matches_cardoid(&mdfc));
+
+    // The value after --columns is never the decklist.
+    let it = Search::try_parse_from(["search", "--columns", "name,cost", "deck.json"])?;
+    assert_eq!(it.columns, vec!["name", "cost"]);
+    assert_eq!(it.decklist_file(), Path::new("deck.json"));
+    assert!(Search::try_parse_from(["search", "--columns"]).is_err());
+
+    // Tags as the sample decks number them, "B.1 - Card Advantage".
+    let card = |name: &str, text: &str| {
+        Cardoid::from(vec![Card {
+            name: name.s(),
+            text: text.s(),
+            legalities: Legalities {
+                commander: Legality::Legal,
+                ..Default::default()
+            },
+            ..Default::default()
+        }])
+    };
+    let mut decklist = DeckList::new();
+    for (name, tag) in [
+        ("God-Eternal Oketra", "A - Commander"),
+        ("Harmonize", "B.1 - Card Advantage"),
+        ("Cultivate", "B.2 - Land Advantage"),
+        ("Swords to Plowshares", "C.1 - Control"),
+    ] {
+        decklist.add_card(Proxy {
+            name: name.s(),
+            tags: IndexSet::from_iter([tag.s()]),
+            cardoid: card(name, ""),
+            ..Default::default()
+        });
+    }
+    let atomics = IndexMap::from_iter(
+        [
+            (
+                "Mind Stone",
+                "{T}: Add {C}. {1}, {T}, Sacrifice Mind Stone: Draw a card.",
+            ),
+            ("Ornithopter", "Flying"),
+            ("Expedition Map", "Search your library for a land card."),
+            ("Harmonize", "Draw three cards."),
+        ]
+        .map(|(name, text)| (name.s(), card(name, text))),
+    );
+    // Commander legality comes from the deck having one.
+    let upgrades = search(&["--upgrades"]);
+    let hits = upgrades.find_upgrades(&atomics, &decklist)?;
+    assert_eq!(
+        hits.iter().map(|c| c.name()).collect_vec(),
+        vec!["Mind Stone", "Expedition Map", "Ornithopter"]
+    );
+
+    // Four Forests and a Harmonize are five cards to --count and the shell.
+    let repeated = |name: &str, repeats| Proxy {
+        name: name.s(),
+        repeats,
+        ..Default::default()
+    };
+    let hits = Hits::Proxies(vec![repeated("Forest", 4), repeated("Harmonize", 1)]);
+    assert_eq!(hits.copies(), 5);
+
+    let mut uncommanded = DeckList::new();
+    uncommanded.add_card(decklist.iter().nth(1).unwrap().clone());
+    assert!(upgrades.find_upgrades(&atomics, &uncommanded).is_err());
+    assert!(search(&["--upgrades", "--legal", "legacy"])
+        .find_upgrades(&atomics, &uncommanded)?
+        .is_empty());
+
+    Ok(())
+}
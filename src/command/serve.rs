@@ -0,0 +1,480 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use itertools::Itertools;
+use tiny_http::{Header, Response, Server};
+
+use crate::{
+    atomic_cards::{cardoids::Cardoid, metadata::Legality, source::CardSource},
+    command::{
+        list::List,
+        search::{CardRow, Column, Hits, Search, Searcher},
+    },
+    html::{Document, Element, Node, Tag},
+    proxy::{decklists::DeckList, Proxy},
+    rendering::{RenderContext, RenderSettings},
+    utils::ToS,
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct Serve {
+    #[arg(long, default_value = "127.0.0.1:8080", value_name = "ADDR")]
+    pub address: String,
+    #[arg(long)]
+    pub in_color: bool,
+    #[arg(value_name = "FILE")]
+    pub decklist: Option<PathBuf>,
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 1em 2em; }
+nav a { margin-right: 1em; }
+table { border-collapse: collapse; }
+td, th { padding: 0.2em 0.8em; text-align: left; }
+tr:nth-child(even) { background: #f0f0f0; }
+iframe { border: none; width: 100%; height: 95vh; }
+.error { color: #a00; }
+";
+
+// Result pages stop here, there's no use rendering the whole database.
+const MAX_RESULTS: usize = 500;
+
+struct Page {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Page {
+    fn html(document: Document) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body: document.to_string(),
+        }
+    }
+
+    fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl Serve {
+    pub fn decklist_file(&self) -> &Path {
+        self.decklist
+            .as_ref()
+            .map(|p| p.as_ref())
+            .unwrap_or(Path::new(""))
+    }
+
+    pub fn dispatch(
+        &self,
+        data_dir: &Path,
+        atomics: &dyn CardSource,
+        decklist: &DeckList,
+    ) -> anyhow::Result<()> {
+        let server = Server::http(&self.address)
+            .map_err(|e| anyhow::anyhow!("Failed to listen on {}: {}", self.address, e))?;
+        eprintln!("Serving on http://{}/", self.address);
+
+        for request in server.incoming_requests() {
+            let (path, params) = parse_url(request.url());
+
+            let page = match self.route(&path, &params, data_dir, atomics, decklist) {
+                Ok(Some(page)) => page,
+                Ok(None) => {
+                    Self::layout("Not found", Element::new(Tag::p).node("Not found.")).status(404)
+                }
+                Err(e) => Self::layout(
+                    "Error",
+                    Element::new(Tag::pre).class(["error"]).node(e.to_string()),
+                )
+                .status(400),
+            };
+
+            let header = Header::from_bytes("Content-Type", page.content_type)
+                .expect("static header is valid");
+            let response = Response::from_string(page.body)
+                .with_status_code(page.status)
+                .with_header(header);
+
+            if let Err(e) = request.respond(response) {
+                eprintln!("Failed to respond: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn route(
+        &self,
+        path: &str,
+        params: &[(String, String)],
+        data_dir: &Path,
+        atomics: &dyn CardSource,
+        decklist: &DeckList,
+    ) -> anyhow::Result<Option<Page>> {
+        let param = |key: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+                .unwrap_or("")
+        };
+
+        Ok(Some(match path {
+            "/" | "/search" => Self::search(param("q"), param("sort"), atomics, &DeckList::new())?,
+            "/deck/search" => Self::search(param("q"), param("sort"), atomics, decklist)?,
+            "/card" => {
+                let Some(cardoid) = atomics.lookup(param("name"))? else {
+                    return Ok(None);
+                };
+                Self::card(&cardoid)
+            }
+            "/card/proxy" => {
+                let proxy = match decklist.iter().find(|p| p.name == param("name")) {
+                    Some(proxy) => Proxy {
+                        repeats: 1,
+                        ..proxy.clone()
+                    },
+                    None => match atomics.lookup(param("name"))? {
+                        Some(cardoid) => Proxy {
+                            name: cardoid.name().s(),
+                            cardoid,
+                            repeats: 1,
+                            reminder_text: true,
+                            ..Default::default()
+                        },
+                        None => return Ok(None),
+                    },
+                };
+                self.render([&proxy], data_dir)?
+            }
+            "/deck" => Self::deck(decklist)?,
+            "/deck/print" => self.render(decklist, data_dir)?,
+            _ => return Ok(None),
+        }))
+    }
+
+    fn layout<N: Into<Node>>(title: &str, content: N) -> Page {
+        let link = |href: &str, text: &str| Element::new(Tag::a).attr("href", href).node(text);
+
+        Page::html(
+            Document::new()
+                .title(format!("{} - proxygenitus", title))
+                .inline_style(STYLE)
+                .body(
+                    Element::new(Tag::nav)
+                        .node(link("/", "Search"))
+                        .node(link("/deck", "Deck"))
+                        .node(link("/deck/print", "Print preview")),
+                )
+                .body(Element::new(Tag::h1).node(title))
+                .body(content),
+        )
+    }
+
+    fn search(
+        query: &str,
+        sort: &str,
+        atomics: &dyn CardSource,
+        decklist: &DeckList,
+    ) -> anyhow::Result<Page> {
+        let input = |name: &'static str, value: &str, placeholder: &str| {
+            Element::new(Tag::input)
+                .attr("name", name)
+                .attr("value", value)
+                .attr("placeholder", placeholder)
+        };
+        let action = if decklist.is_empty() {
+            "/search"
+        } else {
+            "/deck/search"
+        };
+        let form = Element::new(Tag::form)
+            .attr("action", action)
+            .node(
+                input("q", query, "t:elf c:g mv<=2")
+                    .attr("size", 60)
+                    .flag("autofocus"),
+            )
+            .node(input("sort", sort, "mv,name"))
+            .node(Element::new(Tag::button).node("Search"));
+
+        let mut content = Element::new(Tag::div).node(form);
+
+        if !query.is_empty() {
+            // With = so a query starting with -, like -t:land, stays a value.
+            let query = format!("--query={}", query);
+            let mut args = vec!["search", &query];
+            if !sort.is_empty() {
+                args.extend(["--sort", sort]);
+            }
+            let searcher = Searcher::new(Search::try_parse_from(args)?)?;
+            let hits = searcher.find(atomics, decklist)?;
+            let rows = match &hits {
+                Hits::Cards(hits) => hits.iter().map(|c| (CardRow::new(c), c)).collect_vec(),
+                Hits::Proxies(hits) => hits
+                    .iter()
+                    .map(|p| (CardRow::from(p), &p.cardoid))
+                    .collect_vec(),
+            };
+
+            // Copies, as the shell and --count report them.
+            let count = hits.copies();
+            content = content
+                .node(Element::new(Tag::p).node(if rows.len() > MAX_RESULTS {
+                    format!("{} cards, showing the first {}", count, MAX_RESULTS)
+                } else {
+                    format!("{} {}", count, if count == 1 { "card" } else { "cards" })
+                }))
+                .node(Self::card_table(rows.into_iter().take(MAX_RESULTS)));
+        }
+
+        Ok(Self::layout("Search", content))
+    }
+
+    fn card_table<'a>(rows: impl Iterator<Item = (CardRow, &'a Cardoid)>) -> Element {
+        let columns = [
+            Column::Name,
+            Column::Cost,
+            Column::Type,
+            Column::PowerToughness,
+        ];
+
+        let mut table = Element::new(Tag::table);
+        for (row, cardoid) in rows {
+            let mut tr = Element::new(Tag::tr);
+            for column in columns {
+                let cell = column.cell(&row, cardoid);
+                tr = tr.node(
+                    Element::new(Tag::td).node(match column {
+                        Column::Name => Element::new(Tag::a)
+                            .attr("href", format!("/card?name={}", encode(cardoid.name())))
+                            .node(cell)
+                            .into(),
+                        _ => Node::from(cell),
+                    }),
+                );
+            }
+            table = table.node(tr);
+        }
+        table
+    }
+
+    fn card(cardoid: &Cardoid) -> Page {
+        let legalities = cardoid
+            .face()
+            .legalities
+            .formats()
+            .into_iter()
+            .filter(|(_, legality)| *legality != Legality::NotLegal)
+            .map(|(format, legality)| {
+                Element::new(Tag::tr)
+                    .node(Element::new(Tag::td).node(format))
+                    .node(Element::new(Tag::td).node(legality.s()))
+            });
+
+        Self::layout(
+            cardoid.name(),
+            Element::new(Tag::div)
+                .node(Element::new(Tag::pre).node(cardoid.to_string()))
+                .node(Element::new(Tag::table).nodes(legalities))
+                .node(Element::new(Tag::iframe).attr(
+                    "src",
+                    format!("/card/proxy?name={}", encode(cardoid.name())),
+                )),
+        )
+    }
+
+    fn deck(decklist: &DeckList) -> anyhow::Result<Page> {
+        let list = List::try_parse_from([
+            "list",
+            "-",
+            "--id",
+            "--cards",
+            "--sideboard",
+            "--tokens",
+            "--curve",
+            "--colors",
+            "--types",
+            "--tags",
+            "--creature-types",
+            "--lands",
+            "--pips",
+        ])?;
+
+        let reports = list
+            .reports(decklist)
+            .into_iter()
+            .map(|(_, title, report)| report.text(title))
+            .join("\n");
+
+        let rows = decklist.iter().map(|p| (CardRow::from(p), &p.cardoid));
+
+        Ok(Self::layout(
+            "Deck",
+            Element::new(Tag::div)
+                .node(Self::deck_search_form())
+                .node(Element::new(Tag::h2).node("Cards"))
+                .node(Self::card_table(rows))
+                .node(Element::new(Tag::h2).node("Analysis"))
+                .node(Element::new(Tag::pre).node(reports)),
+        ))
+    }
+
+    fn deck_search_form() -> Element {
+        Element::new(Tag::form).attr("action", "/deck/search").node(
+            Element::new(Tag::input)
+                .attr("name", "q")
+                .attr("placeholder", "search the deck"),
+        )
+    }
+
+    // Nothing here may need the network: Scryfall stays off, so only art
+    // already set in the decklist shows, and the fonts and mana symbols are
+    // inlined stand-ins. Art URLs in the decklist still load remotely.
+    fn render<'a>(
+        &self,
+        proxies: impl IntoIterator<Item = &'a Proxy>,
+        data_dir: &Path,
+    ) -> anyhow::Result<Page> {
+        let settings = RenderSettings {
+            in_color: self.in_color,
+            testing: false,
+            remninder_text: None,
+            scryfall: Some(false),
+            offline: true,
+        };
+        let mut render = RenderContext::new(settings, data_dir)?;
+        for proxy in proxies {
+            render.add_proxy(proxy);
+        }
+
+        Ok(Page::html(render.into_file()?))
+    }
+}
+
+fn parse_url(url: &str) -> (String, Vec<(String, String)>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+    let params = query
+        .split('&')
+        .filter(|s| !s.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(k), decode(v))
+        })
+        .collect();
+
+    (decode(path), params)
+}
+
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => res.push(b' '),
+            b'%' => match bytes
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+            {
+                Some(b) => {
+                    res.push(b);
+                    i += 2;
+                }
+                None => res.push(b'%'),
+            },
+            b => res.push(b),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&res).into_owned()
+}
+
+fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_url() {
+    let (path, params) = parse_url("/search?q=t%3Aelf+c%3Ag&sort=&name=L%C3%B3rien%2");
+    assert_eq!(path, "/search");
+    assert_eq!(
+        params,
+        vec![
+            ("q".s(), "t:elf c:g".s()),
+            ("sort".s(), "".s()),
+            ("name".s(), "Lórien%2".s()),
+        ]
+    );
+    assert_eq!(decode(&encode("Fire // Ice")), "Fire // Ice");
+    assert_eq!(encode("Fire // Ice"), "Fire%20%2F%2F%20Ice");
+}
+
+#[test]
+fn test_routes() -> anyhow::Result<()> {
+    use indexmap::{IndexMap, IndexSet};
+
+    use crate::atomic_cards::{cards::Card, mana::ManaCost, types::Type};
+
+    let elves = Cardoid::from(vec![Card {
+        name: "Llanowar Elves".s(),
+        mana_cost: ManaCost::from("{G}"),
+        type_line: "Creature — Elf Druid".s(),
+        types: vec![Type::Creature],
+        text: "{T}: Add {G}.".s(),
+        power: "1".s(),
+        toughness: "1".s(),
+        ..Default::default()
+    }]);
+    let atomics = IndexMap::from_iter([("Llanowar Elves".s(), elves.clone())]);
+    let mut decklist = DeckList::new();
+    decklist.add_card(Proxy {
+        name: "Llanowar Elves".s(),
+        cardoid: elves,
+        repeats: 1,
+        tags: IndexSet::from_iter(["Ramp".s()]),
+        ..Default::default()
+    });
+
+    let serve = Serve::try_parse_from(["serve"])?;
+    let dir = std::env::temp_dir();
+    let get = |url: &str| {
+        let (path, params) = parse_url(url);
+        serve.route(&path, &params, &dir, &atomics, &decklist)
+    };
+    let body = |url: &str| -> anyhow::Result<String> { Ok(get(url)?.expect("route exists").body) };
+
+    assert!(body("/search?q=t%3Aelf")?.contains("1 card<"));
+    assert!(body("/search?q=-t%3Aelf")?.contains("0 cards"));
+    assert!(body("/deck/search?q=tag%3ARamp")?.contains("Llanowar Elves"));
+    assert!(body("/card?name=Llanowar+Elves")?.contains("/card/proxy?name=Llanowar%20Elves"));
+    assert!(body("/deck")?.contains("Analysis"));
+    assert!(get("/card?name=Forest")?.is_none());
+    assert!(get("/nowhere")?.is_none());
+
+    // The print previews carry their own fonts and symbols.
+    for url in ["/card/proxy?name=Llanowar+Elves", "/deck/print"] {
+        let page = body(url)?;
+        assert!(page.contains("Llanowar Elves"));
+        assert!(page.contains("ms-g"));
+        assert!(page.contains(".ms-g::before"));
+        assert!(!page.contains("https://"), "{} links outside", url);
+    }
+
+    Ok(())
+}
@@ -1,11 +1,53 @@
+use std::path::{Path, PathBuf};
+
 use clap::Parser;
 
-#[derive(Parser, Debug)]
-pub struct Setup;
+use crate::atomic_cards::{source::CardSource, AtomicCardsDb, AtomicCardsFile};
+
+#[derive(Parser, Debug, Clone)]
+pub struct Setup {
+    #[arg(long, value_name = "URL", conflicts_with = "file")]
+    pub url: Option<String>,
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<PathBuf>,
+}
 
 impl Setup {
-    #[allow(unused)]
-    fn dispatch() -> anyhow::Result<()> {
-        Ok(())
+    pub fn decklist_file(&self) -> &Path {
+        Path::new("")
+    }
+
+    pub fn dispatch(&self, data_dir: &Path, verbose: bool) -> anyhow::Result<()> {
+        let raw = if let Some(file) = &self.file {
+            eprintln!("Importing {}", file.display());
+            std::fs::read(file)?
+        } else {
+            let url = self
+                .url
+                .as_deref()
+                .unwrap_or(AtomicCardsFile::ATOMIC_CARDS_URL);
+            eprintln!("Downloading {}", url);
+            AtomicCardsFile::download(data_dir, url, verbose)?
+        };
+
+        Self::install(data_dir, &raw)
+    }
+
+    pub fn install(data_dir: &Path, raw: &[u8]) -> anyhow::Result<()> {
+        let atomic_cards: AtomicCardsFile = serde_json::from_slice(raw)?;
+        atomic_cards.save_json(data_dir)?;
+        atomic_cards.try_save_snapshot(data_dir);
+
+        eprintln!("Building card database");
+        let atomics = AtomicCardsDb::build(data_dir, &atomic_cards, true)?;
+
+        let validation = atomics.validate();
+
+        let meta = atomics.meta()?;
+        println!("Cards: {}", atomics.card_count()?);
+        println!("Version: {}", meta.version);
+        println!("Date: {}", meta.date);
+
+        validation
     }
 }
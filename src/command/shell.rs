@@ -0,0 +1,251 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use indexmap::IndexMap;
+use itertools::Itertools;
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+use crate::{
+    atomic_cards::{cardoids::Cardoid, metadata::MetaData, source::CardSource},
+    command::{list::List, search::Search},
+    proxy::{decklists::DeckList, Proxy},
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct Shell {
+    #[arg(value_name = "FILE")]
+    pub decklist: Option<PathBuf>,
+}
+
+const HELP: &str = "\
+Anything that isn't a command is a search query, e.g. t:elf c:g -t:legendary.
+
+  :search FLAGS...        search with the search command's flags, e.g. :search --type elf
+  :deck [QUERY]           list the deck, or search inside it
+  :add NAME [#CATEGORY]   add a card to the deck, also :add \"NAME\" CATEGORY
+  :list REPORT...         print list reports, e.g. :list curve tags
+  :help                   show this message
+  :quit                   leave the shell";
+
+impl Shell {
+    const HISTORY_FILE: &'static str = "shell_history.txt";
+
+    pub fn decklist_file(&self) -> &Path {
+        self.decklist
+            .as_ref()
+            .map(|p| p.as_ref())
+            .unwrap_or(Path::new(""))
+    }
+
+    pub fn dispatch(
+        &self,
+        data_dir: &Path,
+        atomics: &dyn CardSource,
+        decklist: &mut DeckList,
+    ) -> anyhow::Result<()> {
+        let history = data_dir.join(Self::HISTORY_FILE);
+        let mut editor = DefaultEditor::new()?;
+        if history.exists() {
+            editor.load_history(&history)?;
+        }
+
+        let atomics = &Loaded::new(atomics)?;
+        eprintln!(
+            "{} cards loaded, {} in the deck. Type :help for help.",
+            atomics.card_count()?,
+            decklist.count_cards(Proxy::in_deck)
+        );
+
+        loop {
+            let line = match editor.readline("> ") {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(e.into()),
+            };
+
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            editor.add_history_entry(line)?;
+
+            match Self::execute(line, atomics, decklist) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+
+        editor.save_history(&history)?;
+        Ok(())
+    }
+
+    fn execute(
+        line: &str,
+        atomics: &dyn CardSource,
+        decklist: &mut DeckList,
+    ) -> anyhow::Result<bool> {
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match command {
+            ":q" | ":quit" | ":exit" => return Ok(false),
+            ":h" | ":help" => println!("{}", HELP),
+            ":deck" if rest.is_empty() => {
+                List::cards(decklist, Proxy::in_deck).print("Cards");
+            }
+            ":deck" => Self::search(Self::query(rest)?, atomics, decklist)?,
+            ":search" => Self::search(Self::flags(rest)?, atomics, &DeckList::new())?,
+            ":add" => Self::add(rest, atomics, decklist)?,
+            ":list" => {
+                let args = split_words(rest)?.into_iter().map(|w| {
+                    if w.starts_with('-') {
+                        w
+                    } else {
+                        format!("--{}", w.replace('_', "-"))
+                    }
+                });
+                List::try_parse_from(["list", "-"].into_iter().map(String::from).chain(args))?
+                    .dispatch(decklist)?;
+            }
+            _ if command.starts_with(':') => {
+                anyhow::bail!("Unknown command '{}', try :help", command)
+            }
+            _ => Self::search(Self::query(line)?, atomics, &DeckList::new())?,
+        }
+
+        Ok(true)
+    }
+
+    // The whole line is the query, "-t:legendary" included.
+    fn query(line: &str) -> anyhow::Result<Search> {
+        Ok(Search::try_parse_from([
+            "search".to_owned(),
+            format!("--query={}", line),
+        ])?)
+    }
+
+    fn flags(line: &str) -> anyhow::Result<Search> {
+        let args = ["search".to_owned()].into_iter().chain(split_words(line)?);
+        Ok(Search::try_parse_from(args)?)
+    }
+
+    fn search(search: Search, atomics: &dyn CardSource, decklist: &DeckList) -> anyhow::Result<()> {
+        let count = search.run(atomics, decklist)?;
+        println!("\n{} {}", count, if count == 1 { "card" } else { "cards" });
+        Ok(())
+    }
+
+    fn add(args: &str, atomics: &dyn CardSource, decklist: &mut DeckList) -> anyhow::Result<()> {
+        let (name, category) = if args.starts_with('"') {
+            let mut words = split_words(args)?.into_iter();
+            (words.next().unwrap_or_default(), words.join(" "))
+        } else if let Some((name, category)) = args.split_once('#') {
+            (name.trim().to_owned(), category.trim().to_owned())
+        } else {
+            (args.to_owned(), String::new())
+        };
+
+        if name.is_empty() {
+            anyhow::bail!("Usage: :add NAME [#CATEGORY]");
+        }
+
+        // Going through the decklist format resolves names the same way a
+        // decklist file would, including the suggestions for typos.
+        let json = serde_json::json!({ category: [{ "name": name }] });
+        for proxy in DeckList::load_str(&json.to_string(), atomics)? {
+            println!("Added {}", proxy.name);
+            decklist.add_card(proxy);
+        }
+
+        Ok(())
+    }
+}
+
+// Every card in memory for the length of the session, queries that don't
+// compile to SQL would otherwise read the whole database each time. The text
+// index stays in the database.
+struct Loaded<'a> {
+    cards: IndexMap<String, Cardoid>,
+    source: &'a dyn CardSource,
+}
+
+impl<'a> Loaded<'a> {
+    fn new(source: &'a dyn CardSource) -> anyhow::Result<Self> {
+        let mut cards = IndexMap::new();
+        source.for_each_cardoid(&mut |name, cardoid| {
+            cards.insert(name.to_owned(), cardoid.clone());
+            Ok(())
+        })?;
+        Ok(Self { cards, source })
+    }
+}
+
+impl CardSource for Loaded<'_> {
+    fn lookup(&self, cardname: &str) -> anyhow::Result<Option<Cardoid>> {
+        self.cards.lookup(cardname)
+    }
+
+    fn for_each_cardoid(
+        &self,
+        mapper: &mut dyn FnMut(&str, &Cardoid) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.cards.for_each_cardoid(mapper)
+    }
+
+    fn meta(&self) -> anyhow::Result<MetaData> {
+        self.source.meta()
+    }
+
+    fn text_search(&self, query: &str) -> anyhow::Result<Vec<(String, f64)>> {
+        self.source.text_search(query)
+    }
+
+    fn card_count(&self) -> anyhow::Result<usize> {
+        Ok(self.cards.len())
+    }
+}
+
+fn split_words(line: &str) -> anyhow::Result<Vec<String>> {
+    let mut res = vec![];
+    let mut word = None::<String>;
+    let mut quote = None;
+
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_default().push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_default();
+            }
+            (None, c) if c.is_whitespace() => res.extend(word.take()),
+            (None, c) => word.get_or_insert_default().push(c),
+        }
+    }
+
+    if quote.is_some() {
+        anyhow::bail!("Unterminated quote");
+    }
+    res.extend(word);
+    Ok(res)
+}
+
+#[test]
+fn test_split_words() {
+    assert_eq!(
+        split_words(r#""Llanowar Elves" Ramp  --mv '<= 2' """#).unwrap(),
+        vec!["Llanowar Elves", "Ramp", "--mv", "<= 2", ""]
+    );
+    assert!(split_words("\"Fire // Ice").is_err());
+
+    assert_eq!(
+        Shell::query("-t:legendary c:g").unwrap().query.as_deref(),
+        Some("-t:legendary c:g")
+    );
+    assert_eq!(
+        Shell::flags("--type elf --mv '<= 2'").unwrap().r#type,
+        vec!["elf"]
+    );
+}
@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use clap::Parser;
+
+use crate::{atomic_cards::AtomicCardsDb, command::output};
+
+/// Run an SQL query against the card database
+///
+/// The tables are the ones SqliteTableImpl creates: Cardoid, Card,
+/// ForeignData, Legalities and MetaData, plus the CardText search index.
+/// `x REGEXP 'pattern'` works as well.
+#[derive(Parser, Debug, Clone)]
+pub struct Sql {
+    #[arg(value_name = "QUERY")]
+    pub query: String,
+    /// Leave out the row of column names
+    #[arg(long)]
+    pub no_header: bool,
+}
+
+impl Sql {
+    pub fn decklist_file(&self) -> &Path {
+        Path::new("")
+    }
+
+    pub fn dispatch(&self, data_dir: &Path, verbose: bool) -> anyhow::Result<()> {
+        let db = AtomicCardsDb::load_or_build(data_dir, verbose)?;
+        let (columns, mut rows) = db.query_table(&self.query)?;
+
+        if !self.no_header {
+            rows.insert(0, columns);
+        }
+        output::print_table(&rows);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use clap::Parser;
+
+use crate::{
+    atomic_cards::{download::Compression, AtomicCardsDb, AtomicCardsFile},
+    command::setup::Setup,
+};
+
+#[derive(Parser, Debug, Clone)]
+pub struct Update {
+    #[arg(long, value_name = "URL", default_value = AtomicCardsFile::MTGJSON_URL)]
+    pub base_url: String,
+    #[arg(long, value_enum, default_value_t)]
+    pub compression: Compression,
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl Update {
+    pub fn decklist_file(&self) -> &Path {
+        Path::new("")
+    }
+
+    pub fn dispatch(&self, data_dir: &Path, verbose: bool) -> anyhow::Result<()> {
+        let upstream = AtomicCardsFile::fetch_meta(&self.base_url)?;
+
+        match AtomicCardsDb::installed_meta(data_dir)? {
+            Some(installed) if !self.force && !upstream.is_newer_than(&installed) => {
+                println!(
+                    "Card data is up to date: version {} from {}",
+                    installed.version, installed.date
+                );
+                return Ok(());
+            }
+            Some(installed) => eprintln!(
+                "Updating card data from version {} to {}",
+                installed.version, upstream.version
+            ),
+            None => eprintln!("Installing card data version {}", upstream.version),
+        }
+
+        let raw = AtomicCardsFile::download_verified(
+            data_dir,
+            &self.base_url,
+            self.compression,
+            verbose,
+        )?;
+
+        Setup::install(data_dir, &raw)
+    }
+}
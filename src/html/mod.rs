@@ -2,11 +2,9 @@ mod elements;
 mod node;
 mod tags;
 mod tests;
-pub use anyhow::anyhow;
-// use css_minify::optimizations::{Level, Minifier};
 pub use elements::*;
 pub use node::*;
-use std::{fmt::Display, path::Path};
+use std::fmt::Display;
 pub use tags::*;
 
 #[derive(Clone)]
@@ -63,28 +61,11 @@ impl Document {
             .node(Element::new(Tag::body).nodes(self.body))
     }
 
-    pub fn inline_style<P>(self, path: P) -> anyhow::Result<Self>
+    pub fn inline_style<S>(self, css: S) -> Self
     where
-        P: AsRef<Path>,
+        S: AsRef<str>,
     {
-        let input =
-            std::fs::read_to_string(&path).map_err(|e| anyhow!("{:?} - {}", path.as_ref(), e))?;
-
-        // let minified = Minifier::default().minify(&input, Level::Zero);
-
-        // let input = match minified {
-        //     Ok(minified) => minified,
-        //     Err(error) => {
-        //         eprintln!(
-        //             "Failure to minify {}: {}",
-        //             path.as_ref().to_string_lossy(),
-        //             error
-        //         );
-        //         input
-        //     }
-        // };
-
-        Ok(self.head(Element::new(Tag::style).node(input)))
+        self.head(Element::new(Tag::style).node(css))
     }
 }
 
@@ -203,6 +203,11 @@ impl Tag {
         void: false,
         literal: None,
     };
+    pub const h2: Tag = Tag {
+        name: "h2",
+        void: false,
+        literal: None,
+    };
     pub const head: Tag = Tag {
         name: "head",
         void: false,
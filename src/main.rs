@@ -4,32 +4,56 @@ mod atomic_cards;
 mod command;
 mod html;
 mod proxy;
+mod query;
 mod rendering;
 mod scryfall;
 mod utils;
 
 use std::path::Path;
 
-use atomic_cards::AtomicCardsFile;
+use atomic_cards::{homebrew::HomebrewCards, source::CardSource, AtomicCardsDb, AtomicCardsFile};
 use clap::Parser;
 use command::Command;
 
-use crate::proxy::decklists::DeckList;
+use crate::proxy::decklists::{DeckList, DeckListFile};
 
 fn main() -> anyhow::Result<()> {
     let command = Command::parse();
+    let data_dir = command.data_dir()?;
 
-    let atomic_cards = AtomicCardsFile::load_json(command.verbose)?;
-    //atomic_cards.validate()?;
-
+    // Read before the card data is opened, only the cards it names get loaded.
     let decklist_file = command.subcommand.decklist_file();
-    let mut decklist = if decklist_file == Path::new("") {
-        DeckList::new()
+    let deck = if decklist_file == Path::new("") {
+        None
+    } else {
+        Some(DeckListFile::load(decklist_file)?)
+    };
+
+    let mut atomic_cards: Box<dyn CardSource> = if command.subcommand.uses_card_data() {
+        Box::new(AtomicCardsDb::load_or_build(&data_dir, command.verbose)?)
     } else {
-        DeckList::load(decklist_file, &atomic_cards)?
+        Box::new(AtomicCardsFile::default())
+    };
+
+    if !command.cards_file.is_empty() {
+        let homebrew = HomebrewCards::load(atomic_cards, &command.cards_file)?;
+        for collision in homebrew.collisions() {
+            eprintln!("Warning: {}", collision);
+        }
+        atomic_cards = Box::new(homebrew);
+    }
+
+    let mut decklist = match deck {
+        Some(deck) => deck.resolve(atomic_cards.as_ref())?,
+        None => DeckList::new(),
     };
 
-    command.subcommand.dispatch(&atomic_cards, &mut decklist)?;
+    command.subcommand.dispatch(
+        command.verbose,
+        &data_dir,
+        atomic_cards.as_ref(),
+        &mut decklist,
+    )?;
 
     Ok(())
 }
@@ -9,12 +9,11 @@ use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use serde::Deserialize;
 
-use crate::{
-    atomic_cards::{
-        types::{Type, WUBRG},
-        AtomicCardsFile,
-    },
-    utils::ToS,
+use crate::atomic_cards::{
+    cardoids::Cardoid,
+    names::NameIndex,
+    source::CardSource,
+    types::{Type, WUBRG},
 };
 
 use super::Proxy;
@@ -22,6 +21,17 @@ use super::Proxy;
 #[derive(Clone, Debug, Default)]
 pub struct DeckList(Vec<Proxy>);
 
+// Categories are often numbered to keep them in order, "B.1 - Card Advantage"
+// is still the card advantage tag.
+pub fn tag_label(tag: &str) -> &str {
+    match tag.split_once(" -") {
+        Some((prefix, rest)) if !prefix.is_empty() && !prefix.contains(' ') => {
+            rest.trim_start_matches('-').trim()
+        }
+        _ => tag,
+    }
+}
+
 impl DeckList {
     pub fn new() -> DeckList {
         Self(vec![])
@@ -31,17 +41,12 @@ impl DeckList {
         self.0.push(proxy)
     }
 
-    pub fn load_str(data: &str, atomics: &AtomicCardsFile) -> anyhow::Result<DeckList> {
+    pub fn load_str(data: &str, atomics: &dyn CardSource) -> anyhow::Result<DeckList> {
         let structure: DeckListFile = serde_json::from_str(&data)?;
 
         Ok(DeckList(structure.build(atomics)?))
     }
 
-    pub fn load(path: &Path, atomics: &AtomicCardsFile) -> anyhow::Result<DeckList> {
-        let data = std::fs::read_to_string(path)?;
-        Self::load_str(&data, atomics)
-    }
-
     pub fn card_names<F>(&self, filter: F) -> BTreeMap<String, usize>
     where
         F: Fn(&Proxy) -> bool,
@@ -120,6 +125,32 @@ impl DeckList {
         WUBRG(res)
     }
 
+    pub fn commanders(&self) -> Vec<&Proxy> {
+        self.iter()
+            .filter(|p| {
+                p.tags
+                    .iter()
+                    .any(|t| tag_label(t).eq_ignore_ascii_case("commander"))
+            })
+            .collect_vec()
+    }
+
+    // Decks without a card tagged as the commander are limited to the colors
+    // they already play.
+    pub fn commander_identity(&self) -> WUBRG {
+        let commanders = self.commanders();
+        if commanders.is_empty() {
+            return self.color_id();
+        }
+
+        WUBRG(
+            commanders
+                .iter()
+                .flat_map(|p| p.cardoid.color_identity().0.iter().copied())
+                .collect(),
+        )
+    }
+
     pub fn curve(&self) -> BTreeMap<usize, usize> {
         let mut res = BTreeMap::new();
 
@@ -232,16 +263,145 @@ impl<'a> IntoIterator for &'a mut DeckList {
     }
 }
 
+#[test]
+fn test_decklist_load() -> anyhow::Result<()> {
+    use crate::{
+        atomic_cards::{cardoids::Cardoid, cards::Card},
+        utils::ToS,
+    };
+
+    let card = |name: &str, t: Type| {
+        Cardoid::from(vec![Card {
+            name: name.s(),
+            types: vec![t],
+            ..Default::default()
+        }])
+    };
+
+    let atomics = IndexMap::from_iter([
+        ("Forest".s(), card("Forest", Type::Land)),
+        ("Giant Growth".s(), card("Giant Growth", Type::Instant)),
+        ("Fire // Ice".s(), card("Fire", Type::Instant)),
+        (
+            "Lórien Revealed".s(),
+            card("Lórien Revealed", Type::Sorcery),
+        ),
+    ]);
+
+    let decklist = DeckList::load_str(
+        r#"{"Lands": [{"name": "Forest", "repeats": 2}], "": [{"name": "Giant Growth"}]}"#,
+        &atomics,
+    )?;
+
+    assert_eq!(decklist.count_cards(Proxy::in_deck), 3);
+    assert_eq!(
+        decklist.categories(Proxy::in_deck).keys().collect_vec(),
+        vec!["Lands", "Instants"]
+    );
+
+    let decklist = DeckList::load_str(
+        r#"{"": [{"name": "Fire"}, {"name": "lorien revealed"}]}"#,
+        &atomics,
+    )?;
+    assert_eq!(
+        decklist.iter().map(|p| p.name.as_str()).collect_vec(),
+        vec!["Fire // Ice", "Lórien Revealed"]
+    );
+
+    let error = DeckList::load_str(
+        r#"{"": [{"name": "Forrest"}, {"name": "Llanowar Elves"}]}"#,
+        &atomics,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.downcast::<DeckListBuildError>()?.0,
+        vec![
+            "Failed to find: Forrest (did you mean: Forest?)".s(),
+            "Failed to find: Llanowar Elves".s()
+        ]
+    );
+
+    let proxy = |name: &str, colors: &str, tag: &str| Proxy {
+        name: name.s(),
+        tags: IndexSet::from_iter([tag.s()]),
+        cardoid: Cardoid::from(vec![Card {
+            name: name.s(),
+            color_identity: WUBRG::from(colors),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+
+    let mut decklist = DeckList::new();
+    decklist.add_card(proxy("Knight of Autumn", "GW", "Removal"));
+    decklist.add_card(proxy("Lightning Bolt", "R", "Removal"));
+    assert_eq!(decklist.commander_identity(), WUBRG::from("RGW"));
+    decklist.add_card(proxy("Selvala, Heart of the Wilds", "G", "Commander"));
+    assert_eq!(decklist.commander_identity(), WUBRG::from("G"));
+
+    assert_eq!(tag_label("B.1 - Card Advantage"), "Card Advantage");
+    assert_eq!(tag_label("F.2 -- Sideboard"), "Sideboard");
+    assert_eq!(tag_label("Card Advantage"), "Card Advantage");
+
+    // The sample decks, one with numbered categories and one without.
+    for (file, commander) in [
+        ("decklists/oketra.json", "God-Eternal Oketra"),
+        ("decklists/catra.json", "God-Eternal Oketra"),
+    ] {
+        let list = DeckListFile::load(Path::new(file))?;
+        let atomics = IndexMap::from_iter(
+            list.0
+                .values()
+                .flatten()
+                .map(|p| (p.name.clone(), card(&p.name, Type::Creature))),
+        );
+        let decklist = list.resolve(&atomics)?;
+        assert_eq!(
+            decklist
+                .commanders()
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect_vec(),
+            vec![commander]
+        );
+    }
+
+    Ok(())
+}
+
+// A decklist as written, before its cards are looked up.
 #[derive(Deserialize, Clone)]
 #[serde(transparent)]
-struct DeckListFile(IndexMap<String, Vec<Proxy>>);
+pub struct DeckListFile(IndexMap<String, Vec<Proxy>>);
 
 impl DeckListFile {
-    fn build(self, atomics: &AtomicCardsFile) -> anyhow::Result<Vec<Proxy>> {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    pub fn resolve(self, atomics: &dyn CardSource) -> anyhow::Result<DeckList> {
+        Ok(DeckList(self.build(atomics)?))
+    }
+
+    fn build(self, atomics: &dyn CardSource) -> anyhow::Result<Vec<Proxy>> {
         let mut res = vec![];
         let mut errors = vec![];
-
-        Self::build_categorized(self.0, atomics, &mut res, &mut errors);
+        let mut names = None;
+
+        // Everything the list names in one go, the index of all names is only
+        // built for the ones that aren't exact.
+        let wanted = self
+            .0
+            .values()
+            .flatten()
+            .filter(|p| p.cardoid.is_empty())
+            .map(|p| p.name.as_str())
+            .unique()
+            .collect_vec();
+        let found = atomics.lookup_many(&wanted)?;
+
+        Self::build_categorized(self.0, atomics, &found, &mut names, &mut res, &mut errors)?;
 
         if errors.is_empty() {
             Ok(res)
@@ -252,36 +412,71 @@ impl DeckListFile {
 
     fn build_categorized(
         categories: IndexMap<String, Vec<Proxy>>,
-        atomics: &AtomicCardsFile,
+        atomics: &dyn CardSource,
+        found: &IndexMap<String, Cardoid>,
+        names: &mut Option<NameIndex>,
         res: &mut Vec<Proxy>,
         errors: &mut Vec<String>,
-    ) {
+    ) -> anyhow::Result<()> {
         for (category, mut vec) in categories {
             if !category.is_empty() {
                 vec.iter_mut().for_each(|a| {
                     a.tags.insert_before(0, category.clone());
                 });
             }
-            Self::build_uncategorized(vec, atomics, res, errors);
+            Self::build_uncategorized(vec, atomics, found, names, res, errors)?;
         }
+        Ok(())
     }
 
     fn build_uncategorized(
         vec: Vec<Proxy>,
-        atomics: &AtomicCardsFile,
+        atomics: &dyn CardSource,
+        found: &IndexMap<String, Cardoid>,
+        names: &mut Option<NameIndex>,
         res: &mut Vec<Proxy>,
         errors: &mut Vec<String>,
-    ) {
+    ) -> anyhow::Result<()> {
         for mut proxy in vec {
             if proxy.cardoid.is_empty() {
-                let Some(cardoid) = atomics.data.get(&proxy.name).map(Clone::clone) else {
-                    errors.push("Failed to find: ".s() + &proxy.name);
-                    continue;
-                };
-                proxy.cardoid = cardoid;
+                if let Some(cardoid) = found.get(&proxy.name) {
+                    proxy.cardoid = cardoid.clone();
+                } else {
+                    let names = match names {
+                        Some(names) => names,
+                        None => names.insert(NameIndex::load(atomics)?),
+                    };
+
+                    let cardoid = match names.resolve(&proxy.name) {
+                        Some(name) => atomics.lookup(name)?.map(|c| (name, c)),
+                        None => None,
+                    };
+
+                    let Some((name, cardoid)) = cardoid else {
+                        errors.push(Self::not_found(&proxy.name, names));
+                        continue;
+                    };
+                    proxy.name = name.to_owned();
+                    proxy.cardoid = cardoid;
+                }
             }
             res.push(proxy);
         }
+        Ok(())
+    }
+
+    fn not_found(name: &str, names: &NameIndex) -> String {
+        let suggestions = names.suggest(name);
+
+        if suggestions.is_empty() {
+            format!("Failed to find: {}", name)
+        } else {
+            format!(
+                "Failed to find: {} (did you mean: {}?)",
+                name,
+                suggestions.join(", ")
+            )
+        }
     }
 }
 
@@ -18,7 +18,7 @@ use crate::{
     utils::ToS,
 };
 
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Proxy {
     pub name: String,
     #[serde(default, deserialize_with = "OneOrMany::<Art>::one_or_many")]
@@ -126,7 +126,7 @@ impl Display for Proxy {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Art {
     #[serde(default)]
     pub url: String,
@@ -150,7 +150,7 @@ impl Art {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Customization {
     #[serde(default, rename = "flavorText")]
     pub flavor_text: Option<String>,
@@ -0,0 +1,155 @@
+use std::str::FromStr;
+
+use crate::atomic_cards::cards::Card;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    Ge,
+    Gt,
+}
+
+impl CompareOp {
+    pub fn split(s: &str) -> Option<(CompareOp, &str)> {
+        [
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("!=", CompareOp::Ne),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+            ("=", CompareOp::Eq),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| s.strip_prefix(prefix).map(|rest| (op, rest)))
+    }
+
+    pub fn compare<T: PartialOrd>(&self, value: T, n: T) -> bool {
+        match self {
+            CompareOp::Lt => value < n,
+            CompareOp::Le => value <= n,
+            CompareOp::Eq => value == n,
+            CompareOp::Ne => value != n,
+            CompareOp::Ge => value >= n,
+            CompareOp::Gt => value > n,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareValue {
+    Number(f64),
+    Star,
+    X,
+}
+
+// Numeric comparisons treat `*` and `X` as 0, the way X is everywhere but the
+// stack, so "1+*" compares as 1. To look for the variable itself, use `=*` or
+// `=X`, which match any stat (or, for mana value, any cost) containing it.
+// Empty stats, like a sorcery's power, never match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub op: CompareOp,
+    pub value: CompareValue,
+}
+
+impl FromStr for Comparison {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (op, rest) = CompareOp::split(s).unwrap_or((CompareOp::Eq, s));
+        Self::new(op, rest)
+    }
+}
+
+impl Comparison {
+    pub fn new(op: CompareOp, value: &str) -> anyhow::Result<Self> {
+        let value = match value.trim() {
+            "*" => CompareValue::Star,
+            "X" | "x" => CompareValue::X,
+            n => CompareValue::Number(
+                n.parse()
+                    .map_err(|_| anyhow::anyhow!("Not a number: {}", value))?,
+            ),
+        };
+
+        match (op, value) {
+            (_, CompareValue::Number(_)) | (CompareOp::Eq | CompareOp::Ne, _) => {
+                Ok(Self { op, value })
+            }
+            _ => Err(anyhow::anyhow!("Only = and != work with * and X")),
+        }
+    }
+
+    pub fn matches(&self, stat: &str) -> bool {
+        if stat.is_empty() {
+            return false;
+        }
+
+        match self.value {
+            CompareValue::Star => self.compare_eq(stat.contains('*')),
+            CompareValue::X => self.compare_eq(stat.contains('X')),
+            CompareValue::Number(n) => self.op.compare(Self::stat_value(stat), n),
+        }
+    }
+
+    pub fn matches_mana_value(&self, card: &Card) -> bool {
+        match self.value {
+            CompareValue::Star => self.compare_eq(false),
+            CompareValue::X => self.compare_eq(card.mana_cost.has_variable()),
+            CompareValue::Number(n) => self
+                .op
+                .compare(card.face_mana_value.unwrap_or(card.mana_value), n),
+        }
+    }
+
+    pub fn stat_value(stat: &str) -> f64 {
+        if stat.contains('∞') {
+            return f64::INFINITY;
+        }
+        lazy_regex::regex!(r"[+-]?\d+(?:\.\d+)?")
+            .find(stat)
+            .and_then(|m| m.as_str().parse().ok())
+            .unwrap_or(0.0)
+    }
+
+    fn compare_eq(&self, found: bool) -> bool {
+        found == (self.op == CompareOp::Eq)
+    }
+}
+
+#[test]
+fn test_comparisons() {
+    let cmp = |s: &str| s.parse::<Comparison>().unwrap();
+
+    assert!(cmp("<=3").matches("3"));
+    assert!(!cmp("<3").matches("3"));
+    assert!(cmp(">4").matches("5"));
+    assert!(cmp("4").matches("4"));
+    assert!(cmp("!=4").matches("3"));
+    assert!(cmp(">=-1").matches("-1"));
+    assert!(cmp(">=1").matches("1+*"));
+    assert!(cmp("=0").matches("*"));
+    assert!(cmp("=*").matches("1+*"));
+    assert!(!cmp("=*").matches("1"));
+    assert!(cmp("!=*").matches("1"));
+    assert!(cmp("=X").matches("X"));
+    assert!(cmp(">100").matches("∞"));
+    assert!(!cmp(">=0").matches(""));
+    assert!(!cmp("!=*").matches(""));
+
+    assert!("<X".parse::<Comparison>().is_err());
+    assert!("<=three".parse::<Comparison>().is_err());
+
+    let card = Card {
+        mana_cost: "{X}{R}".into(),
+        mana_value: 1.0,
+        ..Default::default()
+    };
+    assert!(cmp("=X").matches_mana_value(&card));
+    assert!(cmp("<=1").matches_mana_value(&card));
+    assert!(!cmp("=*").matches_mana_value(&card));
+}
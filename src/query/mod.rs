@@ -0,0 +1,270 @@
+pub mod comparison;
+pub mod parser;
+pub mod sort;
+pub mod sql;
+
+use indexmap::IndexSet;
+use regex::Regex;
+
+use itertools::Itertools;
+
+use crate::atomic_cards::{
+    cardoids::Cardoid,
+    cards::Card,
+    mana::ManaCost,
+    metadata::{Legalities, Legality},
+    types::{LeadershipSkills, WUBRG},
+};
+
+use comparison::{CompareOp, Comparison};
+
+#[derive(Debug, Clone)]
+pub enum Query {
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+    // Holds when a single face matches the whole query, the way the old
+    // --type/--text/--color flags combine.
+    Face(Box<Query>),
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Name(Regex),
+    ExactName(String),
+    Type(Regex),
+    Oracle(Regex),
+    Grep(Regex),
+    Keyword(String),
+    Tag(String),
+    Color(CompareOp, WUBRG),
+    Identity(CompareOp, WUBRG),
+    Mana(ManaCost),
+    ManaValue(Comparison),
+    Power(Comparison),
+    Toughness(Comparison),
+    Loyalty(Comparison),
+    Defense(Comparison),
+    Legality(String, Legality),
+    CanLead(String),
+}
+
+#[derive(Clone, Copy)]
+pub struct Subject<'a> {
+    pub cardoid: &'a Cardoid,
+    pub tags: Option<&'a IndexSet<String>>,
+    pub language: Option<&'a str>,
+    // Face filters only look at this card when set.
+    pub face: Option<&'a Card>,
+}
+
+impl<'a> Subject<'a> {
+    pub fn new(cardoid: &'a Cardoid) -> Self {
+        Self {
+            cardoid,
+            tags: None,
+            language: None,
+            face: None,
+        }
+    }
+
+    pub fn names(&self) -> Vec<&'a str> {
+        let mut res = vec![self.cardoid.name()];
+
+        for card in self.cardoid.iter() {
+            for foreign in &card.foreign_data {
+                if let Some(language) = self.language {
+                    if !foreign.language.eq_ignore_ascii_case(language) {
+                        continue;
+                    }
+                }
+                res.extend([foreign.name.as_str(), foreign.face_name.as_str()]);
+            }
+        }
+
+        res.retain(|n| !n.is_empty());
+        res
+    }
+}
+
+impl Query {
+    pub fn all(mut queries: Vec<Query>) -> Query {
+        if queries.len() == 1 {
+            queries.pop().unwrap()
+        } else {
+            Query::And(queries)
+        }
+    }
+
+    pub fn not(query: Query) -> Query {
+        Query::Not(Box::new(query))
+    }
+
+    pub fn matches(&self, subject: &Subject) -> bool {
+        match self {
+            Query::And(queries) => queries.iter().all(|q| q.matches(subject)),
+            Query::Or(queries) => queries.iter().any(|q| q.matches(subject)),
+            Query::Not(query) => !query.matches(subject),
+            Query::Face(query) => subject.cardoid.iter().any(|card| {
+                query.matches(&Subject {
+                    face: Some(card),
+                    ..*subject
+                })
+            }),
+            Query::Filter(filter) => filter.matches(subject),
+        }
+    }
+}
+
+impl From<Filter> for Query {
+    fn from(value: Filter) -> Self {
+        Query::Filter(value)
+    }
+}
+
+impl Filter {
+    pub fn legality(format: &str, legality: Legality) -> anyhow::Result<Filter> {
+        let format = format.to_ascii_lowercase();
+        if Legalities::default().format(&format).is_none() {
+            let formats = Legalities::default().formats().map(|(f, _)| f);
+            anyhow::bail!(
+                "Unknown format '{}', expected one of: {}",
+                format,
+                formats.join(", ")
+            );
+        }
+        Ok(Filter::Legality(format, legality))
+    }
+
+    pub fn can_lead(format: &str) -> anyhow::Result<Filter> {
+        let format = match format.to_ascii_lowercase().as_str() {
+            "brawler" => "brawl".to_owned(),
+            format => format.to_owned(),
+        };
+        if LeadershipSkills::default().format(&format).is_none() {
+            anyhow::bail!(
+                "Unknown commander format '{}', expected one of: {}",
+                format,
+                LeadershipSkills::FORMATS.iter().join(", ")
+            );
+        }
+        Ok(Filter::CanLead(format))
+    }
+
+    pub fn matches(&self, subject: &Subject) -> bool {
+        let cardoid = subject.cardoid;
+
+        match self {
+            Filter::Name(r) => subject.names().iter().any(|n| r.is_match(n)),
+            Filter::ExactName(name) => {
+                cardoid.name().eq_ignore_ascii_case(name)
+                    || cardoid
+                        .iter()
+                        .any(|c| c.face_name.eq_ignore_ascii_case(name))
+            }
+            Filter::Grep(r) => r.is_match(&cardoid.to_string()),
+            Filter::Tag(tag) => subject.tags.is_some_and(|tags| tags.contains(tag)),
+            Filter::Identity(op, colors) => compare_colors(*op, cardoid.color_identity(), colors),
+            _ => match subject.face {
+                Some(card) => self.matches_card(card),
+                None => cardoid.iter().any(|card| self.matches_card(card)),
+            },
+        }
+    }
+
+    fn matches_card(&self, card: &Card) -> bool {
+        match self {
+            Filter::Type(r) => r.is_match(&card.type_line),
+            Filter::Oracle(r) => r.is_match(&card.text),
+            Filter::Keyword(k) => card.keywords.iter().any(|kw| kw.eq_ignore_ascii_case(k)),
+            Filter::Color(op, colors) => compare_colors(*op, &card.colors, colors),
+            Filter::Mana(cost) => card.mana_cost.contains(cost),
+            Filter::ManaValue(c) => c.matches_mana_value(card),
+            Filter::Power(c) => c.matches(&card.power),
+            Filter::Toughness(c) => c.matches(&card.toughness),
+            Filter::Loyalty(c) => c.matches(&card.loyalty),
+            Filter::Defense(c) => c.matches(&card.defense),
+            // Restricted cards are still legal to play, just limited to one copy.
+            Filter::Legality(format, legality) => match card.legalities.format(format) {
+                Some(Legality::Restricted) if *legality == Legality::Legal => true,
+                Some(found) => found == *legality,
+                None => false,
+            },
+            Filter::CanLead(format) => card.leadership_skills.format(format) == Some(true),
+            _ => false,
+        }
+    }
+}
+
+pub fn compare_colors(op: CompareOp, colors: &WUBRG, other: &WUBRG) -> bool {
+    let (a, b) = (&colors.0, &other.0);
+    match op {
+        CompareOp::Eq => a == b,
+        CompareOp::Ne => a != b,
+        CompareOp::Le => a.is_subset(b),
+        CompareOp::Lt => a.is_subset(b) && a != b,
+        CompareOp::Ge => a.is_superset(b),
+        CompareOp::Gt => a.is_superset(b) && a != b,
+    }
+}
+
+#[test]
+fn test_query_matches() {
+    use crate::utils::ToS;
+
+    let cardoid = Cardoid::from(vec![Card {
+        name: "Knight of Autumn".s(),
+        type_line: "Creature — Dryad Knight".s(),
+        text: "When this creature enters, choose one —".s(),
+        colors: WUBRG::from("GW"),
+        color_identity: WUBRG::from("GW"),
+        keywords: IndexSet::from_iter(["Flying".s()]),
+        power: "2".s(),
+        ..Default::default()
+    }]);
+    let subject = Subject::new(&cardoid);
+    let regex = |s: &str| Regex::new(s).unwrap();
+    let colors = |op, s: &str| Filter::Color(op, WUBRG::from(s)).into();
+
+    let matches = |q: Query| q.matches(&subject);
+
+    assert!(matches(colors(CompareOp::Ge, "G")));
+    assert!(matches(colors(CompareOp::Eq, "GW")));
+    assert!(!matches(colors(CompareOp::Lt, "GW")));
+    assert!(matches(colors(CompareOp::Lt, "WUG")));
+    assert!(matches(
+        Filter::Identity(CompareOp::Le, WUBRG::wubrg()).into()
+    ));
+    assert!(matches(Query::And(vec![
+        Filter::Type(regex("Knight")).into(),
+        Filter::Power(">=2".parse().unwrap()).into(),
+    ])));
+    assert!(matches(Query::Or(vec![
+        Filter::Keyword("reach".s()).into(),
+        Filter::Keyword("flying".s()).into(),
+    ])));
+    assert!(!matches(Query::not(Filter::Oracle(regex("enters")).into())));
+    assert!(!matches(Filter::Tag("Ramp".s()).into()));
+    assert!(matches(Filter::ExactName("knight of autumn".s()).into()));
+    assert!(matches(Query::And(vec![])));
+
+    let mut cardoid = cardoid.clone();
+    for card in cardoid.iter_mut() {
+        card.legalities.vintage = Legality::Restricted;
+        card.legalities.legacy = Legality::Banned;
+        card.leadership_skills.brawl = true;
+    }
+    let subject = Subject::new(&cardoid);
+    let matches = |f: anyhow::Result<Filter>| Query::from(f.unwrap()).matches(&subject);
+
+    assert!(matches(Filter::legality("Vintage", Legality::Legal)));
+    assert!(matches(Filter::legality("vintage", Legality::Restricted)));
+    assert!(matches(Filter::legality("legacy", Legality::Banned)));
+    assert!(!matches(Filter::legality("legacy", Legality::Legal)));
+    assert!(!matches(Filter::legality("modern", Legality::Legal)));
+    assert!(matches(Filter::can_lead("brawler")));
+    assert!(!matches(Filter::can_lead("commander")));
+    assert!(Filter::legality("extended", Legality::Legal).is_err());
+    assert!(Filter::can_lead("planechase").is_err());
+}
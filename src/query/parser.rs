@@ -0,0 +1,457 @@
+use std::{error::Error, fmt::Display};
+
+use regex::Regex;
+
+use crate::atomic_cards::{mana::ManaCost, metadata::Legality, types::WUBRG};
+
+use super::{
+    comparison::{CompareOp, Comparison},
+    Filter, Query,
+};
+
+#[derive(Debug, Clone)]
+pub struct QueryError {
+    pub query: String,
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let column = self.query[..self.position].chars().count();
+        writeln!(f, "Invalid query: {}", self.message)?;
+        writeln!(f, "  {}", self.query)?;
+        write!(f, "  {}^", " ".repeat(column))
+    }
+}
+
+impl Error for QueryError {}
+
+pub fn parse(query: &str, case_sensitive: bool) -> Result<Query, QueryError> {
+    let mut parser = Parser {
+        query,
+        pos: 0,
+        case_sensitive,
+    };
+
+    let res = parser.parse_or()?;
+    parser.skip_whitespace();
+    if parser.pos < query.len() {
+        return parser.error(parser.pos, "Unexpected ')'");
+    }
+    Ok(res)
+}
+
+enum Value {
+    Text(String),
+    Regex(String),
+}
+
+struct Parser<'a> {
+    query: &'a str,
+    pos: usize,
+    case_sensitive: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, position: usize, message: impl Into<String>) -> Result<T, QueryError> {
+        Err(QueryError {
+            query: self.query.to_owned(),
+            position,
+            message: message.into(),
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.query[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.query.len() - self.rest().trim_start().len();
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        let rest = self.rest();
+        rest.get(..keyword.len())
+            .is_some_and(|w| w.eq_ignore_ascii_case(keyword))
+            && rest[keyword.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace() || c == '(')
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        if self.at_keyword(keyword) {
+            self.pos += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut queries = vec![self.parse_and()?];
+        while self.eat_keyword("or") {
+            queries.push(self.parse_and()?);
+        }
+        Ok(if queries.len() == 1 {
+            queries.pop().unwrap()
+        } else {
+            Query::Or(queries)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut queries = vec![];
+
+        loop {
+            self.skip_whitespace();
+            if matches!(self.peek(), None | Some(')')) || self.at_keyword("or") {
+                break;
+            }
+            if !queries.is_empty() && self.eat_keyword("and") {
+                continue;
+            }
+            queries.push(self.parse_unary()?);
+        }
+
+        if queries.is_empty() {
+            return self.error(self.pos, "Expected a search term");
+        }
+        Ok(Query::all(queries))
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, QueryError> {
+        self.skip_whitespace();
+        let start = self.pos;
+
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                // "- t:land" would otherwise negate an empty name search.
+                if self.peek().is_none_or(|c| c.is_whitespace() || c == ')') {
+                    return self.error(start, "Expected a search term after '-'");
+                }
+                Ok(Query::not(self.parse_unary()?))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let res = self.parse_or()?;
+                self.skip_whitespace();
+                if self.peek() != Some(')') {
+                    return self.error(start, "Unclosed '('");
+                }
+                self.pos += 1;
+                Ok(res)
+            }
+            _ => self.parse_term(),
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Query, QueryError> {
+        let start = self.pos;
+
+        if self.peek() == Some('!') {
+            self.pos += 1;
+            return match self.parse_value()? {
+                Value::Text(name) if !name.is_empty() => Ok(Filter::ExactName(name).into()),
+                _ => self.error(start, "Expected a card name after '!'"),
+            };
+        }
+
+        let key_len = self
+            .rest()
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(self.rest().len());
+        let key = self.rest()[..key_len].to_ascii_lowercase();
+        let after_key = &self.rest()[key_len..];
+
+        let op = if key.is_empty() {
+            None
+        } else if let Some(rest) = after_key.strip_prefix(':') {
+            Some((None, rest))
+        } else {
+            CompareOp::split(after_key).map(|(op, rest)| (Some(op), rest))
+        };
+
+        let Some((op, rest)) = op else {
+            return match self.parse_value()? {
+                Value::Text(name) => Ok(Filter::Name(self.text_regex(&name)).into()),
+                Value::Regex(re) => Ok(Filter::Name(self.regex(start, &re)?).into()),
+            };
+        };
+
+        self.pos = self.query.len() - rest.len();
+        let value_start = self.pos;
+        let value = self.parse_value()?;
+        if matches!(&value, Value::Text(t) if t.is_empty()) {
+            return self.error(value_start, format!("Missing value for '{}'", key));
+        }
+
+        self.filter(start, &key, op, value).map(Query::Filter)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, QueryError> {
+        let start = self.pos;
+
+        for (delim, what) in [('"', "quote"), ('/', "regex")] {
+            if self.peek() != Some(delim) {
+                continue;
+            }
+            let body = &self.rest()[1..];
+            let Some(end) = body.find(delim) else {
+                return self.error(start, format!("Unterminated {}", what));
+            };
+            self.pos += end + 2;
+            return Ok(match delim {
+                '"' => Value::Text(body[..end].to_owned()),
+                _ => Value::Regex(body[..end].to_owned()),
+            });
+        }
+
+        let end = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .unwrap_or(self.rest().len());
+        let res = self.rest()[..end].to_owned();
+        self.pos += end;
+        Ok(Value::Text(res))
+    }
+
+    fn filter(
+        &self,
+        start: usize,
+        key: &str,
+        op: Option<CompareOp>,
+        value: Value,
+    ) -> Result<Filter, QueryError> {
+        let pattern = |value: Value| match value {
+            Value::Text(text) => Ok(self.text_regex(&text)),
+            Value::Regex(re) => self.regex(start, &re),
+        };
+        let text = |value: Value| match value {
+            Value::Text(text) => Ok(text),
+            Value::Regex(_) => self.error(start, format!("'{}' does not take a regex", key)),
+        };
+        let comparison = |value: Value| {
+            let value = text(value)?;
+            Comparison::new(op.unwrap_or(CompareOp::Eq), &value)
+                .or_else(|e| self.error(start, e.to_string()))
+        };
+        let colors = |value: Value| {
+            let value = text(value)?;
+            parse_colors(&value).map_or_else(
+                || self.error(start, format!("Unknown colors '{}'", value)),
+                Ok,
+            )
+        };
+
+        let text_op = match op {
+            None | Some(CompareOp::Eq) => Ok(()),
+            Some(_) => self.error(start, format!("'{}' only supports ':'", key)),
+        };
+
+        let format = |value: Value, filter: &dyn Fn(&str) -> anyhow::Result<Filter>| {
+            let value = text_op.clone().and(text(value))?;
+            filter(&value).or_else(|e| self.error(start, e.to_string()))
+        };
+
+        Ok(match key {
+            "n" | "name" => text_op.and(pattern(value)).map(Filter::Name)?,
+            "t" | "type" => text_op.and(pattern(value)).map(Filter::Type)?,
+            "o" | "oracle" | "text" => text_op.and(pattern(value)).map(Filter::Oracle)?,
+            "grep" => text_op.and(pattern(value)).map(Filter::Grep)?,
+            "kw" | "keyword" => text_op.and(text(value)).map(Filter::Keyword)?,
+            "tag" => text_op.and(text(value)).map(Filter::Tag)?,
+            "m" | "mana" => text_op
+                .and(text(value))
+                .map(|v| Filter::Mana(ManaCost::parse_shorthand(&v)))?,
+            "c" | "color" | "colors" => {
+                let colors = colors(value)?;
+                let op = match op {
+                    Some(op) => op,
+                    None if colors.0.is_empty() => CompareOp::Eq,
+                    None => CompareOp::Ge,
+                };
+                Filter::Color(op, colors)
+            }
+            "id" | "ci" | "identity" => {
+                Filter::Identity(op.unwrap_or(CompareOp::Le), colors(value)?)
+            }
+            "mv" | "cmc" | "manavalue" => Filter::ManaValue(comparison(value)?),
+            "pow" | "power" => Filter::Power(comparison(value)?),
+            "tou" | "toughness" => Filter::Toughness(comparison(value)?),
+            "loy" | "loyalty" => Filter::Loyalty(comparison(value)?),
+            "def" | "defense" => Filter::Defense(comparison(value)?),
+            "f" | "format" | "legal" => format(value, &|f| Filter::legality(f, Legality::Legal))?,
+            "banned" => format(value, &|f| Filter::legality(f, Legality::Banned))?,
+            "restricted" => format(value, &|f| Filter::legality(f, Legality::Restricted))?,
+            "is" => format(value, &Filter::can_lead)?,
+            _ => return self.error(start, format!("Unknown search key '{}'", key)),
+        })
+    }
+
+    fn text_regex(&self, text: &str) -> Regex {
+        let case = if self.case_sensitive { "" } else { "(?i)" };
+        Regex::new(&format!("{}{}", case, regex::escape(text))).unwrap()
+    }
+
+    fn regex(&self, start: usize, re: &str) -> Result<Regex, QueryError> {
+        let case = if self.case_sensitive { "" } else { "(?i)" };
+        Regex::new(&format!("{}{}", case, re)).or_else(|e| self.error(start, e.to_string()))
+    }
+}
+
+pub fn parse_colors(value: &str) -> Option<WUBRG> {
+    let value = value.to_ascii_lowercase();
+    let letters = match value.as_str() {
+        "c" | "colorless" => "",
+        "white" => "w",
+        "blue" => "u",
+        "black" => "b",
+        "red" => "r",
+        "green" => "g",
+        "azorius" => "wu",
+        "dimir" => "ub",
+        "rakdos" => "br",
+        "gruul" => "rg",
+        "selesnya" => "gw",
+        "orzhov" => "wb",
+        "izzet" => "ur",
+        "golgari" => "bg",
+        "boros" => "rw",
+        "simic" => "gu",
+        "bant" => "gwu",
+        "esper" => "wub",
+        "grixis" => "ubr",
+        "jund" => "brg",
+        "naya" => "rgw",
+        "abzan" => "wbg",
+        "jeskai" => "urw",
+        "sultai" => "bgu",
+        "mardu" => "rwb",
+        "temur" => "gur",
+        letters if letters.chars().all(|c| "wubrg".contains(c)) => letters,
+        _ => return None,
+    };
+    Some(WUBRG::from(letters))
+}
+
+#[test]
+fn test_parse_query() {
+    use crate::{
+        atomic_cards::{cardoids::Cardoid, cards::Card},
+        query::Subject,
+        utils::ToS,
+    };
+    use indexmap::IndexSet;
+
+    let card = |name: &str, type_line: &str, colors: &str, mv: f64, keyword: &str| {
+        Cardoid::from(vec![Card {
+            name: name.s(),
+            type_line: type_line.s(),
+            text: "When this enters, draw a card.".s(),
+            colors: WUBRG::from(colors),
+            color_identity: WUBRG::from(colors),
+            mana_value: mv,
+            keywords: IndexSet::from_iter([keyword.s()]),
+            ..Default::default()
+        }])
+    };
+
+    let cards = [
+        card("Wall of Omens", "Creature — Wall", "W", 2.0, "Defender"),
+        card("Thassa's Oracle", "Creature — Merfolk Wizard", "U", 2.0, ""),
+        card(
+            "Raffine",
+            "Legendary Creature — Sphinx Demon",
+            "WUB",
+            4.0,
+            "Flying",
+        ),
+        card("Mulldrifter", "Creature — Elemental", "U", 5.0, "Flying"),
+        card("Seeker", "Creature — Bird", "WU", 3.0, "Flying"),
+    ];
+
+    let search = |q: &str| {
+        let query = parse(q, false).unwrap();
+        cards
+            .iter()
+            .filter(|c| query.matches(&Subject::new(c)))
+            .map(|c| c.name())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        search(
+            r#"t:creature c>=wu id<=esper mv<=3 o:"draw a card" -t:legendary (kw:flying or kw:reach)"#
+        ),
+        vec!["Seeker"]
+    );
+    assert_eq!(
+        search("c=u or c=w mv=2"),
+        vec!["Wall of Omens", "Thassa's Oracle", "Mulldrifter"]
+    );
+    assert_eq!(search("(c=u or c=w) mv>4"), vec!["Mulldrifter"]);
+    assert_eq!(search("oracle -t:/creature/"), Vec::<&str>::new());
+    assert_eq!(search("oracle"), vec!["Thassa's Oracle"]);
+    assert_eq!(search("!\"wall of omens\""), vec!["Wall of Omens"]);
+    assert_eq!(search("id:azorius and mv:3"), vec!["Seeker"]);
+
+    let error = |q: &str| parse(q, false).unwrap_err().to_string();
+
+    assert_eq!(
+        error("t:creature foo:bar"),
+        "Invalid query: Unknown search key 'foo'\n  t:creature foo:bar\n             ^"
+    );
+    assert_eq!(
+        error("(c:u or c:w"),
+        "Invalid query: Unclosed '('\n  (c:u or c:w\n  ^"
+    );
+    assert_eq!(
+        error("o:\"draw"),
+        "Invalid query: Unterminated quote\n  o:\"draw\n    ^"
+    );
+    assert_eq!(
+        error("mv<=three"),
+        "Invalid query: Not a number: three\n  mv<=three\n  ^"
+    );
+    assert_eq!(
+        error("c:purple"),
+        "Invalid query: Unknown colors 'purple'\n  c:purple\n  ^"
+    );
+    assert_eq!(
+        error("t:"),
+        "Invalid query: Missing value for 't'\n  t:\n    ^"
+    );
+    assert_eq!(
+        error("c:u)"),
+        "Invalid query: Unexpected ')'\n  c:u)\n     ^"
+    );
+    assert_eq!(
+        error("t:creature -"),
+        "Invalid query: Expected a search term after '-'\n  t:creature -\n             ^"
+    );
+    assert_eq!(
+        error("- t:land"),
+        "Invalid query: Expected a search term after '-'\n  - t:land\n  ^"
+    );
+    assert_eq!(
+        error("(c:u -)"),
+        "Invalid query: Expected a search term after '-'\n  (c:u -)\n       ^"
+    );
+    assert_eq!(
+        error("t<=land"),
+        "Invalid query: 't' only supports ':'\n  t<=land\n  ^"
+    );
+    assert!(parse("f:modern banned:legacy is:commander", false).is_ok());
+    assert_eq!(
+        error("is:planeswalker"),
+        "Invalid query: Unknown commander format 'planeswalker', expected one of: commander, brawl, oathbreaker\n  is:planeswalker\n  ^"
+    );
+}
@@ -0,0 +1,173 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use crate::{
+    atomic_cards::{cardoids::Cardoid, cards::Card},
+    query::comparison::Comparison,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Name,
+    ManaValue,
+    Color,
+    Type,
+    Power,
+    Toughness,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub reverse: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (reverse, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+
+        let field = match name.to_ascii_lowercase().as_str() {
+            "name" => SortField::Name,
+            "mv" | "cmc" | "manavalue" => SortField::ManaValue,
+            "color" | "colors" => SortField::Color,
+            "type" => SortField::Type,
+            "pow" | "power" => SortField::Power,
+            "tou" | "toughness" => SortField::Toughness,
+            _ => anyhow::bail!(
+                "Unknown sort key '{}', expected one of: name, mv, color, type, power, toughness",
+                name
+            ),
+        };
+
+        Ok(Self { field, reverse })
+    }
+}
+
+impl SortKey {
+    pub fn compare(&self, a: &Cardoid, b: &Cardoid) -> Ordering {
+        let res = match self.field {
+            SortField::Name => a.name().cmp(b.name()),
+            SortField::ManaValue => a.face().mana_value.total_cmp(&b.face().mana_value),
+            // Mono-colored cards come before multicolored ones, colorless last.
+            SortField::Color => {
+                let colors = |c: &Cardoid| {
+                    let colors = &c.face().colors.0;
+                    (colors.is_empty(), colors.len(), colors.clone())
+                };
+                colors(a).cmp(&colors(b))
+            }
+            SortField::Type => a.face().types.cmp(&b.face().types),
+            SortField::Power => return self.compare_stat(a, b, |c| &c.power),
+            SortField::Toughness => return self.compare_stat(a, b, |c| &c.toughness),
+        };
+
+        if self.reverse {
+            res.reverse()
+        } else {
+            res
+        }
+    }
+
+    pub fn compare_all(keys: &[SortKey], a: &Cardoid, b: &Cardoid) -> Ordering {
+        keys.iter()
+            .map(|key| key.compare(a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    // Cards without the stat sort after the ones that have it, either way.
+    fn compare_stat(&self, a: &Cardoid, b: &Cardoid, stat: fn(&Card) -> &String) -> Ordering {
+        let (a, b) = (stat(a.face()), stat(b.face()));
+        let value = Comparison::stat_value(a).total_cmp(&Comparison::stat_value(b));
+        let value = if self.reverse { value.reverse() } else { value };
+
+        a.is_empty().cmp(&b.is_empty()).then(value)
+    }
+}
+
+#[test]
+fn test_sort_keys() {
+    use crate::{
+        atomic_cards::types::{Type, WUBRG},
+        utils::ToS,
+    };
+
+    let card = |name: &str, mana_value: f64, colors: &str, power: &str| {
+        Cardoid::from(vec![Card {
+            name: name.s(),
+            mana_value,
+            colors: WUBRG::from(colors),
+            power: power.s(),
+            ..Default::default()
+        }])
+    };
+    let mut cards = vec![
+        card("Wall of Omens", 2.0, "W", "0"),
+        card("Sol Ring", 1.0, "", ""),
+        card("Mulldrifter", 5.0, "U", "2"),
+        card("Knight of Autumn", 3.0, "GW", "2"),
+    ];
+
+    let sort = |cards: &mut Vec<Cardoid>, keys: &str| {
+        let keys = keys
+            .split(',')
+            .map(|k| k.parse().unwrap())
+            .collect::<Vec<SortKey>>();
+        cards.sort_by(|a, b| SortKey::compare_all(&keys, a, b));
+        cards.iter().map(|c| c.name().s()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        sort(&mut cards, "-mv"),
+        [
+            "Mulldrifter",
+            "Knight of Autumn",
+            "Wall of Omens",
+            "Sol Ring"
+        ]
+    );
+    assert_eq!(
+        sort(&mut cards, "color,name"),
+        [
+            "Wall of Omens",
+            "Mulldrifter",
+            "Knight of Autumn",
+            "Sol Ring"
+        ]
+    );
+    assert_eq!(
+        sort(&mut cards, "-power,name"),
+        [
+            "Knight of Autumn",
+            "Mulldrifter",
+            "Wall of Omens",
+            "Sol Ring"
+        ]
+    );
+    assert!("rarity".parse::<SortKey>().is_err());
+
+    // Alphabetical, whatever the enum stores, with unknown types last.
+    let typed = |name: &str, t: Type| {
+        Cardoid::from(vec![Card {
+            name: name.s(),
+            types: vec![t],
+            ..Default::default()
+        }])
+    };
+    let mut cards = vec![
+        typed("Unknown", Type::Unsupported),
+        typed("Vanguard", Type::Vanguard),
+        typed("Sorcery", Type::Sorcery),
+        typed("Conspiracy", Type::Conspiracy),
+        typed("Creature", Type::Creature),
+    ];
+    assert_eq!(
+        sort(&mut cards, "type"),
+        ["Conspiracy", "Creature", "Sorcery", "Vanguard", "Unknown"]
+    );
+}
@@ -0,0 +1,391 @@
+use itertools::Itertools;
+use regex::Regex;
+use rusqlite::{functions::FunctionFlags, types::Value, Connection};
+
+use crate::{
+    atomic_cards::{
+        cardoids::Cardoid,
+        cards::Card,
+        metadata::{ForeignData, Legalities, Legality},
+        sqlite::SqliteTable,
+        types::WUBRG,
+    },
+    query::{
+        comparison::{CompareOp, CompareValue},
+        Filter, Query,
+    },
+};
+
+// A WHERE clause over the Cardoid table. It selects at least every matching
+// cardoid, and exactly those when `exact` is set. Filters SQL can't express
+// are left out of an AND, so the results still have to be checked with
+// `Query::matches`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sql {
+    pub clause: String,
+    pub params: Vec<Value>,
+    pub exact: bool,
+}
+
+impl Query {
+    pub fn to_sql(&self) -> Option<Sql> {
+        match self {
+            Query::And(queries) => {
+                let compiled = queries.iter().map(Query::to_sql).collect_vec();
+                let exact = compiled.iter().all(|s| s.as_ref().is_some_and(|s| s.exact));
+                Sql::join(compiled.into_iter().flatten(), "AND", exact)
+            }
+            Query::Or(queries) => {
+                let compiled = queries
+                    .iter()
+                    .map(Query::to_sql)
+                    .collect::<Option<Vec<_>>>()?;
+                let exact = compiled.iter().all(|s| s.exact);
+                Sql::join(compiled, "OR", exact)
+            }
+            Query::Not(query) => match query.to_sql()? {
+                Sql {
+                    clause,
+                    params,
+                    exact: true,
+                } => Some(Sql {
+                    clause: format!("NOT ({})", clause),
+                    params,
+                    exact: true,
+                }),
+                _ => None,
+            },
+            // Each part only says some face matches, not the same one. A
+            // negated part could rule out the wrong face, so those are left
+            // to Query::matches.
+            Query::Face(query) => {
+                let parts = match query.as_ref() {
+                    Query::And(queries) => queries.as_slice(),
+                    query => std::slice::from_ref(query),
+                };
+                let compiled = parts
+                    .iter()
+                    .filter(|q| !q.negates())
+                    .filter_map(Query::to_sql)
+                    .collect_vec();
+                Sql::join(compiled, "AND", false)
+            }
+            Query::Filter(filter) => filter.to_sql(),
+        }
+    }
+
+    fn negates(&self) -> bool {
+        match self {
+            Query::And(queries) | Query::Or(queries) => queries.iter().any(Query::negates),
+            Query::Not(_) => true,
+            Query::Face(query) => query.negates(),
+            Query::Filter(_) => false,
+        }
+    }
+}
+
+impl Filter {
+    fn to_sql(&self) -> Option<Sql> {
+        let regex = |column: &str, r: &Regex| {
+            (
+                format!("{} REGEXP ?1", column),
+                vec![Value::Text(r.as_str().to_owned())],
+            )
+        };
+        let foreign = ForeignData::table_name();
+        let legalities = Legalities::table_name();
+
+        let (condition, params, exact) = match self {
+            // Foreign names of every language count, the language is checked
+            // afterwards.
+            Filter::Name(r) => (
+                format!(
+                    "name REGEXP ?1 OR rowid IN \
+                     (SELECT parent_card FROM {foreign} WHERE name REGEXP ?1 OR face_name REGEXP ?1)"
+                ),
+                vec![Value::Text(r.as_str().to_owned())],
+                false,
+            ),
+            Filter::ExactName(name) => (
+                "name = ?1 COLLATE NOCASE OR face_name = ?1 COLLATE NOCASE".to_owned(),
+                vec![Value::Text(name.clone())],
+                true,
+            ),
+            Filter::Type(r) => {
+                let (condition, params) = regex("type_line", r);
+                (condition, params, true)
+            }
+            Filter::Oracle(r) => {
+                let (condition, params) = regex("text", r);
+                (condition, params, true)
+            }
+            Filter::Color(op, colors) => (color_sql("colors", *op, colors), vec![], true),
+            Filter::Identity(op, colors) => {
+                (color_sql("color_identity", *op, colors), vec![], true)
+            }
+            Filter::ManaValue(comparison) => match comparison.value {
+                CompareValue::Number(n) => (
+                    format!(
+                        "coalesce(face_mana_value, mana_value) {} ?1",
+                        op_sql(comparison.op)
+                    ),
+                    vec![Value::Real(n)],
+                    true,
+                ),
+                _ => return None,
+            },
+            Filter::Legality(format, legality) => {
+                // Checked again since the format becomes a column name.
+                Legalities::default().format(format)?;
+                let values = match legality {
+                    Legality::Legal => "2, 1".to_owned(),
+                    legality => (*legality as i8).to_string(),
+                };
+                (
+                    format!(
+                        "legalities IN (SELECT rowid FROM {legalities} WHERE {format} IN ({values}))"
+                    ),
+                    vec![],
+                    true,
+                )
+            }
+            _ => return None,
+        };
+
+        Some(Sql {
+            clause: format!(
+                "{}.rowid IN (SELECT cardoid FROM {} WHERE {})",
+                Cardoid::table_name(),
+                Card::table_name(),
+                condition
+            ),
+            params,
+            exact,
+        })
+    }
+}
+
+impl Sql {
+    fn join(parts: impl IntoIterator<Item = Sql>, op: &str, exact: bool) -> Option<Sql> {
+        let parts = parts.into_iter().collect_vec();
+        if parts.is_empty() {
+            return None;
+        }
+
+        // Every part numbers its parameters from ?1, so they're shifted past
+        // the ones before.
+        let mut params = vec![];
+        let mut clauses = vec![];
+        for part in parts {
+            let offset = params.len();
+            let clause =
+                lazy_regex::regex!(r"\?(\d+)").replace_all(&part.clause, |c: &regex::Captures| {
+                    format!("?{}", c[1].parse::<usize>().unwrap_or_default() + offset)
+                });
+            clauses.push(format!("({})", clause));
+            params.extend(part.params);
+        }
+
+        Some(Sql {
+            clause: clauses.join(&format!(" {} ", op)),
+            params,
+            exact,
+        })
+    }
+}
+
+fn color_sql(column: &str, op: CompareOp, colors: &WUBRG) -> String {
+    let has = |present: bool| {
+        let checks = WUBRG::wubrg()
+            .0
+            .into_iter()
+            .filter(|c| colors.0.contains(c) == present)
+            .map(|c| {
+                format!(
+                    "instr({}, '{}') {}",
+                    column,
+                    c,
+                    if present { "> 0" } else { "= 0" }
+                )
+            })
+            .collect_vec();
+        if checks.is_empty() {
+            "1".to_owned()
+        } else {
+            format!("({})", checks.join(" AND "))
+        }
+    };
+    let (subset, superset) = (has(false), has(true));
+
+    match op {
+        CompareOp::Le => subset,
+        CompareOp::Ge => superset,
+        CompareOp::Eq => format!("{} AND {}", subset, superset),
+        CompareOp::Ne => format!("NOT ({} AND {})", subset, superset),
+        CompareOp::Lt => format!("{} AND NOT {}", subset, superset),
+        CompareOp::Gt => format!("{} AND NOT {}", superset, subset),
+    }
+}
+
+fn op_sql(op: CompareOp) -> &'static str {
+    match op {
+        CompareOp::Lt => "<",
+        CompareOp::Le => "<=",
+        CompareOp::Eq => "=",
+        CompareOp::Ne => "!=",
+        CompareOp::Ge => ">=",
+        CompareOp::Gt => ">",
+    }
+}
+
+// `x REGEXP y` is how SQLite spells regexp(y, x), compiled once per statement.
+pub fn register_functions(conn: &Connection) -> anyhow::Result<()> {
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let regex = ctx.get_or_create_aux(0, |pattern| -> Result<_, regex::Error> {
+                Regex::new(pattern.as_str().unwrap_or_default())
+            })?;
+            let text = ctx.get_raw(1).as_str().unwrap_or_default();
+            Ok(regex.is_match(text))
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_query_sql() -> anyhow::Result<()> {
+    use crate::{
+        atomic_cards::{
+            cardoids::Cardoid_Keys,
+            sqlite::SqliteTableImpl,
+            types::{Side, WUBRG},
+        },
+        query::{parser, Subject},
+        utils::ToS,
+    };
+    use rusqlite::params_from_iter;
+
+    let card = |name: &str, colors: &str, type_line: &str, text: &str, mana_value: f64| Card {
+        name: name.s(),
+        colors: WUBRG::from(colors),
+        color_identity: WUBRG::from(colors),
+        type_line: type_line.s(),
+        text: text.s(),
+        mana_value,
+        legalities: Legalities {
+            commander: Legality::Legal,
+            vintage: if name == "Sol Ring" {
+                Legality::Restricted
+            } else {
+                Legality::Legal
+            },
+            legacy: if name == "Sol Ring" {
+                Legality::Banned
+            } else {
+                Legality::Legal
+            },
+            ..Default::default()
+        },
+        foreign_data: vec![ForeignData {
+            language: "German".s(),
+            name: format!("{} auf Deutsch", name),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let fire_ice = |side, face_mana_value| Card {
+        face_name: if side == Side::A { "Fire" } else { "Ice" }.s(),
+        side,
+        face_mana_value: Some(face_mana_value),
+        ..card("Fire // Ice", "R", "Instant", "Fire deals 2 damage", 4.0)
+    };
+    let cardoids = vec![
+        Cardoid::from(vec![card("Sol Ring", "", "Artifact", "Add {C}{C}.", 1.0)]),
+        Cardoid::from(vec![card(
+            "Llanowar Elves",
+            "G",
+            "Creature — Elf Druid",
+            "{T}: Add {G}.",
+            1.0,
+        )]),
+        Cardoid::from(vec![card(
+            "Knight of Autumn",
+            "GW",
+            "Creature — Dryad Knight",
+            "When this creature enters, choose one —",
+            3.0,
+        )]),
+        Cardoid::from(vec![fire_ice(Side::A, 2.0), fire_ice(Side::B, 2.0)]),
+    ];
+
+    let conn = Connection::open_in_memory()?;
+    Cardoid::setup(&conn)?;
+    register_functions(&conn)?;
+    Cardoid::store_rows(&conn, |mut s| {
+        for c in &cardoids {
+            s.store(
+                c,
+                &mut Cardoid_Keys {
+                    card_name: c.name().s(),
+                },
+            )?;
+        }
+        Ok(())
+    })?;
+
+    let select = |sql: &Sql| -> anyhow::Result<Vec<String>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT card_name FROM Cardoid WHERE {} ORDER BY rowid;",
+            sql.clause
+        ))?;
+        let res = stmt
+            .query_map(params_from_iter(&sql.params), |r| r.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(res)
+    };
+
+    for (query, exact) in [
+        ("t:creature", true),
+        ("o:add -t:artifact", true),
+        ("c:g", true),
+        ("c=gw or c:c", true),
+        ("c<gw", true),
+        ("id<=r", true),
+        ("mv<=2", true),
+        ("mv>=3 or mv=1", true),
+        ("f:vintage", true),
+        ("restricted:vintage or banned:legacy", true),
+        ("-f:legacy", true),
+        ("!Ice", true),
+        ("!\"llanowar elves\"", true),
+        ("n:deutsch", false),
+        ("kw:flying or t:elf", false),
+        ("c:g kw:reach", false),
+        ("-(t:elf kw:reach)", false),
+    ] {
+        let parsed = parser::parse(query, false)?;
+        let matching = cardoids
+            .iter()
+            .filter(|c| parsed.matches(&Subject::new(c)))
+            .map(|c| c.name().s())
+            .collect_vec();
+
+        match parsed.to_sql() {
+            Some(sql) => {
+                assert_eq!(sql.exact, exact, "{}", query);
+                let selected = select(&sql)?;
+                if exact {
+                    assert_eq!(selected, matching, "{}", query);
+                } else {
+                    assert!(matching.iter().all(|n| selected.contains(n)), "{}", query);
+                }
+            }
+            None => assert!(!exact, "{}", query),
+        }
+    }
+
+    Ok(())
+}
@@ -5,11 +5,11 @@ use crate::{
     html::*,
     proxy::{Art, Customization, Proxy, TextStyle},
     rendering::{
-        manafont::ManaFontSymbolics,
+        manafont::{mana_symbol, ManaFontSymbolics},
         notation::{NoReminderText, PowerToughnessNobreak, ReminderText},
     },
     utils::{
-        symbolics::{replace_symbols, replace_symbols_with, Symchain},
+        symbolics::{replace_symbols, Symchain},
         ToS,
     },
 };
@@ -43,7 +43,7 @@ pub fn title_bar_div(card: &Card, proxy: &Proxy) -> Vec<Element> {
 pub fn mana_cost_span(card: &Card, _proxy: &Proxy) -> Element {
     Element::new(Tag::span)
         .class(["cost"])
-        .nodes(replace_symbols_with(&ManaFontSymbolics, &card.mana_cost))
+        .nodes(card.mana_cost.iter().map(mana_symbol))
 }
 
 pub fn corner_bubble<N>(content: N) -> Element
@@ -151,12 +151,7 @@ pub fn type_line_span(card: &Card, proxy: &Proxy) -> Element {
 
 pub fn color_indicator_span(card: &Card, _proxy: &Proxy) -> Element {
     Element::new(Tag::span).class(["indicator"]).nodes(
-        if !card
-            .colors
-            .0
-            .iter()
-            .all(|c| card.mana_cost.contains(&c.s()))
-            || card.layout == CardLayout::Token
+        if !card.colors.0.is_subset(&card.mana_cost.colors().0) || card.layout == CardLayout::Token
         {
             Some(Element::new(Tag::i).class(vec![
                 "ms".s(),
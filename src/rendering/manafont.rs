@@ -1,8 +1,9 @@
 use regex::Regex;
 
 use crate::{
+    atomic_cards::{mana::ManaSymbol, types::Pie},
     html::{Element, Node, Tag},
-    rendering::parsing::{colored_mana, generic_mana, hybrid_mana, loyalty_symbol, tap_untap},
+    rendering::parsing::{loyalty_symbol, tap_untap},
     utils::{symbolics::SymbolReplacer, ToS},
 };
 
@@ -17,10 +18,9 @@ impl SymbolReplacer for ManaFontSymbolics {
     }
 
     fn map_symbol(&self, matched: &str) -> Vec<Node> {
-        vec![colored_mana(matched)
+        vec![mana_symbol_class(&matched.into())
+            .map(|c| ms_cost_shadow(&c))
             .or_else(|| tap_untap(matched))
-            .or_else(|| hybrid_mana(matched))
-            .or_else(|| generic_mana(matched))
             .or_else(|| loyalty_symbol(matched))
             .map(|s| s.into())
             .unwrap_or_else(|| matched.s().into())]
@@ -31,6 +31,29 @@ impl SymbolReplacer for ManaFontSymbolics {
     }
 }
 
+pub fn mana_symbol(symbol: &ManaSymbol) -> Node {
+    match mana_symbol_class(symbol) {
+        Some(c) => ms_cost_shadow(&c).into(),
+        None => symbol.to_string().into(),
+    }
+}
+
+fn mana_symbol_class(symbol: &ManaSymbol) -> Option<String> {
+    let pie = |p: &Pie| p.to_string().to_lowercase();
+
+    Some(match symbol {
+        ManaSymbol::Generic(n @ 0..=20) => n.to_string(),
+        ManaSymbol::Variable('X') => "x".s(),
+        ManaSymbol::Colored(a) => pie(a),
+        ManaSymbol::Colorless => "c".s(),
+        ManaSymbol::Snow => "s".s(),
+        ManaSymbol::Hybrid(a, b) => pie(a) + &pie(b),
+        ManaSymbol::Phyrexian(a) => pie(a) + "p",
+        ManaSymbol::Twobrid(a) => "2".s() + &pie(a),
+        _ => return None,
+    })
+}
+
 pub fn ms_cost_shadow(c: &str) -> Element {
     Element::new(Tag::i).class(vec![
         "ms".s(),
@@ -8,6 +8,8 @@ pub mod parsing;
 pub mod tokens;
 pub mod verticalia;
 
+use std::path::Path;
+
 use normal::normal_layout_proxy;
 
 use crate::{
@@ -29,8 +31,19 @@ pub struct RenderSettings {
     pub testing: bool,
     pub remninder_text: Option<bool>,
     pub scryfall: Option<bool>,
+    // Inline stand-ins for the web fonts and mana-font instead of linking them.
+    pub offline: bool,
 }
 
+const PAGE_LAYOUT_CSS: &str = include_str!("../../css/page-layout.css");
+const FONT_SETTINGS_CSS: &str = include_str!("../../css/font-settings.css");
+const CARD_GEOMETRY_CSS: &str = include_str!("../../css/card-geometry.css");
+const CARD_LANDSCAPE_CSS: &str = include_str!("../../css/card-landscape.css");
+const FULL_COLOR_CSS: &str = include_str!("../../css/full-color.css");
+const MONOCHROME_CSS: &str = include_str!("../../css/monochrome.css");
+const CARD_COLORS_CSS: &str = include_str!("../../css/card-colors.css");
+const OFFLINE_SYMBOLS_CSS: &str = include_str!("../../css/offline-symbols.css");
+
 pub struct RenderContext {
     pub settings: RenderSettings,
     pub scryfall_client: ScryfallClient,
@@ -38,10 +51,10 @@ pub struct RenderContext {
 }
 
 impl RenderContext {
-    pub fn new(settings: RenderSettings) -> anyhow::Result<Self> {
+    pub fn new(settings: RenderSettings, dir: &Path) -> anyhow::Result<Self> {
         Ok(Self {
             settings,
-            scryfall_client: ScryfallClient::new()?,
+            scryfall_client: ScryfallClient::new(dir)?,
             cards: vec![],
         })
     }
@@ -62,12 +75,17 @@ impl RenderContext {
     }
 
     pub fn into_file(mut self) -> anyhow::Result<Document> {
-        let mut html_pages = Document::new()
-            .title("PROXIES")
-            .head_link("preconnect", "https://fonts.googleapis.com")
-            .head(Element::new(Tag::link).attr("rel", "preconnect").attr("href", "https://fonts.gstatic.com").flag("crossorigin"))
-            .head_link("stylesheet", "https://fonts.googleapis.com/css2?family=Amarante&family=Crimson+Text:ital,wght@0,400;0,600;0,700;1,400;1,600;1,700&family=Inconsolata:wght@200..900&display=swap")
-            .head_link("stylesheet", "https://cdn.jsdelivr.net/npm/mana-font@latest/css/mana.css");
+        let mut html_pages = Document::new().title("PROXIES");
+
+        if self.settings.offline {
+            html_pages = html_pages.inline_style(OFFLINE_SYMBOLS_CSS);
+        } else {
+            html_pages = html_pages
+                .head_link("preconnect", "https://fonts.googleapis.com")
+                .head(Element::new(Tag::link).attr("rel", "preconnect").attr("href", "https://fonts.gstatic.com").flag("crossorigin"))
+                .head_link("stylesheet", "https://fonts.googleapis.com/css2?family=Amarante&family=Crimson+Text:ital,wght@0,400;0,600;0,700;1,400;1,600;1,700&family=Inconsolata:wght@200..900&display=swap")
+                .head_link("stylesheet", "https://cdn.jsdelivr.net/npm/mana-font@latest/css/mana.css");
+        }
 
         if self.settings.testing {
             html_pages = html_pages
@@ -84,17 +102,17 @@ impl RenderContext {
             .head_link("stylesheet", "../css/card-colors.css");
         } else {
             html_pages = html_pages
-                .inline_style("./css/page-layout.css")?
-                .inline_style("./css/font-settings.css")?
-                .inline_style("./css/card-geometry.css")?
-                .inline_style("./css/card-landscape.css")?;
+                .inline_style(PAGE_LAYOUT_CSS)
+                .inline_style(FONT_SETTINGS_CSS)
+                .inline_style(CARD_GEOMETRY_CSS)
+                .inline_style(CARD_LANDSCAPE_CSS);
 
             html_pages = if self.settings.in_color {
-                html_pages.inline_style("./css/full-color.css")?
+                html_pages.inline_style(FULL_COLOR_CSS)
             } else {
-                html_pages.inline_style("./css/monochrome.css")?
+                html_pages.inline_style(MONOCHROME_CSS)
             }
-            .inline_style("./css/card-colors.css")?;
+            .inline_style(CARD_COLORS_CSS);
         }
 
         let mut pages = vec![];
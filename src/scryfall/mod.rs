@@ -3,6 +3,7 @@ mod tags;
 
 use std::{
     collections::{btree_map::Entry, BTreeMap},
+    path::{Path, PathBuf},
     thread,
     time::{Duration, Instant},
 };
@@ -17,18 +18,21 @@ pub struct ScryfallClient {
     pub client: Client,
     pub last: Instant,
     pub database: BTreeMap<String, ScryfallCard>,
+    pub path: PathBuf,
 }
 
 impl ScryfallClient {
     const DELAY: Duration = Duration::from_millis(150);
     const SCRYFALL_FILE: &str = "ScryfallCache.json";
 
-    pub fn new() -> anyhow::Result<Self> {
+    pub fn new(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(Self::SCRYFALL_FILE);
+
         let client = reqwest::blocking::ClientBuilder::new()
             .timeout(None)
             .build()?;
 
-        let database = match std::fs::read_to_string(Self::SCRYFALL_FILE) {
+        let database = match std::fs::read_to_string(&path) {
             Ok(file) => serde_json::from_str(&file)?,
             Err(_) => BTreeMap::new(),
         };
@@ -39,11 +43,12 @@ impl ScryfallClient {
                 .checked_sub(Self::DELAY)
                 .unwrap_or_else(Instant::now),
             database,
+            path,
         })
     }
 
     pub fn save(&mut self) -> anyhow::Result<()> {
-        std::fs::write(Self::SCRYFALL_FILE, serde_json::to_string(&self.database)?)?;
+        std::fs::write(&self.path, serde_json::to_string(&self.database)?)?;
         Ok(())
     }
 